@@ -0,0 +1,134 @@
+//! Compares spawning many instances of a prototype one at a time and as a batch
+// criterion's macros generate undocumented functions, and the components are never read
+#![allow(missing_docs, dead_code)]
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rantz_proto::prelude::*;
+use serde::Deserialize;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+struct BulletManifest {
+    name: String,
+    i: i32,
+}
+
+impl Manifest for BulletManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    type Output = Bullet;
+
+    fn reify(&self) -> Self::Output {
+        Bullet {
+            name: self.name.clone(),
+            damage: self.i,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct Bullet {
+    name: String,
+    damage: i32,
+}
+
+// Batches clone the components through reflection, so they need to be registered
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Damage(i32);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Speed(f32);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct Lifetime(f32);
+
+impl Prototype for Bullet {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn build(&self, target: &mut EntityWorldMut) {
+        target
+            .insert(Name::new(self.name.clone()))
+            .insert(Damage(self.damage))
+            .insert(Speed(10.0))
+            .insert(Lifetime(2.0))
+            .insert(Transform::default());
+    }
+
+    fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), ProtoPlugin::new()))
+        .register_type::<Damage>()
+        .register_type::<Speed>()
+        .register_type::<Lifetime>()
+        .register_type::<Transform>()
+        .add_prototype::<BulletManifest, Bullet>("items.ron");
+
+    for _ in 0..5_000 {
+        app.update();
+        if !app
+            .world()
+            .resource::<PrototypeLibrary<Bullet>>()
+            .is_empty()
+        {
+            return app;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    panic!("items.ron never loaded");
+}
+
+/// Times `spawn`, clearing the spawned entities between iterations
+fn time(app: &mut App, iters: u64, spawn: impl Fn(&mut World)) -> Duration {
+    let mut total = Duration::ZERO;
+    for _ in 0..iters {
+        let start = Instant::now();
+        spawn(app.world_mut());
+        total += start.elapsed();
+        app.world_mut().clear_entities();
+    }
+    total
+}
+
+fn spawn_batch(c: &mut Criterion) {
+    let mut app = app();
+    let id = Id::<Bullet>::from_name("Foo");
+
+    let mut group = c.benchmark_group("spawn_batch");
+    for count in [100, 1_000, 5_000] {
+        group.bench_with_input(BenchmarkId::new("each", count), &count, |b, &count| {
+            b.iter_custom(|iters| {
+                time(&mut app, iters, |world| {
+                    for _ in 0..count {
+                        world.spawn_prototype_id(id);
+                    }
+                })
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", count), &count, |b, &count| {
+            b.iter_custom(|iters| {
+                time(&mut app, iters, |world| {
+                    world.spawn_prototype_batch(id, count);
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, spawn_batch);
+criterion_main!(benches);
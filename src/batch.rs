@@ -0,0 +1,131 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// An extension trait for [`Commands`] and [`World`]
+/// to spawn many instances of one prototype from the [`PrototypeLibrary`].
+///
+/// [`Prototype::build`] only runs on the first instance, and the components it added are cloned
+/// into the rest through reflection, so this assumes `build` adds the same components every time it's called.
+/// If a component isn't registered with `#[reflect(Component)]`, or references other entities
+/// such as [`Children`], `build` runs on every instance instead.
+/// If the prototype has a template, it's cloned into every instance.
+///
+/// If the prototype is missing the [`MissingPrototypePolicy`] decides what happens
+pub trait SpawnPrototypeBatchExt {
+    /// Spawns `count` instances of the prototype `id`, and returns their entities
+    fn spawn_prototype_batch<P: Prototype>(&mut self, id: Id<P>, count: usize) -> Vec<Entity>;
+
+    /// Spawns an instance of the prototype `id` for each bundle in `overrides`, and returns their entities.
+    /// Each bundle is inserted after the instance is built, so it overrides the prototype's components
+    fn spawn_prototype_batch_with<P, B, I>(&mut self, id: Id<P>, overrides: I) -> Vec<Entity>
+    where
+        P: Prototype,
+        B: Bundle,
+        I: IntoIterator<Item = B>;
+}
+
+impl<'w, 's> SpawnPrototypeBatchExt for Commands<'w, 's> {
+    fn spawn_prototype_batch<P: Prototype>(&mut self, id: Id<P>, count: usize) -> Vec<Entity> {
+        let entities: Vec<Entity> = (0..count).map(|_| self.spawn_empty().id()).collect();
+        let batch = entities.clone();
+        self.add(move |world: &mut World| build_batch(world, id, &batch));
+        entities
+    }
+
+    fn spawn_prototype_batch_with<P, B, I>(&mut self, id: Id<P>, overrides: I) -> Vec<Entity>
+    where
+        P: Prototype,
+        B: Bundle,
+        I: IntoIterator<Item = B>,
+    {
+        let batch: Vec<(Entity, B)> = overrides
+            .into_iter()
+            .map(|bundle| (self.spawn_empty().id(), bundle))
+            .collect();
+        let entities = batch.iter().map(|(entity, _)| *entity).collect();
+        self.add(move |world: &mut World| {
+            let entities: Vec<Entity> = batch.iter().map(|(entity, _)| *entity).collect();
+            build_batch(world, id, &entities);
+            insert_batch(world, batch);
+        });
+        entities
+    }
+}
+
+impl SpawnPrototypeBatchExt for World {
+    fn spawn_prototype_batch<P: Prototype>(&mut self, id: Id<P>, count: usize) -> Vec<Entity> {
+        let entities: Vec<Entity> = (0..count).map(|_| self.spawn_empty().id()).collect();
+        build_batch(self, id, &entities);
+        entities
+    }
+
+    fn spawn_prototype_batch_with<P, B, I>(&mut self, id: Id<P>, overrides: I) -> Vec<Entity>
+    where
+        P: Prototype,
+        B: Bundle,
+        I: IntoIterator<Item = B>,
+    {
+        let batch: Vec<(Entity, B)> = overrides
+            .into_iter()
+            .map(|bundle| (self.spawn_empty().id(), bundle))
+            .collect();
+        let entities: Vec<Entity> = batch.iter().map(|(entity, _)| *entity).collect();
+        build_batch(self, id, &entities);
+        insert_batch(self, batch);
+        entities
+    }
+}
+
+/// Builds the prototype `id` on each of the empty `entities`
+pub(crate) fn build_batch<P: Prototype>(world: &mut World, id: Id<P>, entities: &[Entity]) {
+    // Entities can be despawned before a batch spawned with commands is built
+    let entities: Vec<Entity> = entities
+        .iter()
+        .copied()
+        .filter(|&entity| world.get_entity(entity).is_some())
+        .collect();
+    let Some((&first, rest)) = entities.split_first() else {
+        return;
+    };
+    let Some(proto) = resolve_prototype(world, id) else {
+        return;
+    };
+
+    if world
+        .resource::<PrototypeLibrary<P>>()
+        .has_template(&proto.id())
+    {
+        for &entity in &entities {
+            instantiate(&mut world.entity_mut(entity), &proto);
+        }
+        return;
+    }
+
+    build_prototype(&mut world.entity_mut(first), &proto);
+    let Some(template) = ComponentTemplate::snapshot(world, first, &proto) else {
+        for &entity in rest {
+            build_prototype(&mut world.entity_mut(entity), &proto);
+        }
+        return;
+    };
+
+    // Every instance starts empty, so it gets the same components, tracked the same way
+    let Some(tracking) = world.get::<PrototypeComponents>(first).cloned() else {
+        return;
+    };
+    let origin = (FromPrototype(proto.id()), tracking);
+    insert_batch(world, rest.iter().map(|&entity| (entity, origin.clone())));
+    for &entity in rest {
+        template.apply(&mut world.entity_mut(entity));
+    }
+}
+
+/// Inserts each bundle into its entity, logging the entities that were despawned
+fn insert_batch<B: Bundle>(world: &mut World, batch: impl IntoIterator<Item = (Entity, B)>) {
+    if let Err(invalid) = world.insert_or_spawn_batch(batch) {
+        error!(
+            "Failed to insert into entities {:?} spawned from a prototype, they were despawned",
+            invalid
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Sent when loading or reloading manifest files adds, changes or removes prototypes.
+/// A prototype has changed if its [`Prototype::content_hash`] is different
+#[derive(Event, Debug, Clone)]
+pub struct PrototypesChanged<P: Prototype> {
+    /// Prototypes that weren't in the library before
+    pub added: Vec<Id<P>>,
+    /// Prototypes whose content changed
+    pub changed: Vec<Id<P>>,
+    /// Prototypes that are no longer in the library
+    pub removed: Vec<Id<P>>,
+}
+
+impl<P: Prototype> PrototypesChanged<P> {
+    /// Returns true if no prototypes were added, changed or removed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    /// Returns every added, changed and removed prototype
+    pub fn ids(&self) -> impl Iterator<Item = &Id<P>> {
+        self.added
+            .iter()
+            .chain(self.changed.iter())
+            .chain(self.removed.iter())
+    }
+
+    /// Returns true if the prototype was added, changed or removed
+    pub fn contains(&self, id: &Id<P>) -> bool {
+        self.ids().any(|changed| changed == id)
+    }
+
+    /// Adds the changes from `other`, a later update of the library
+    pub(crate) fn extend(&mut self, other: Self) {
+        for id in other.added {
+            match self.removed.iter().position(|removed| *removed == id) {
+                Some(index) => {
+                    self.removed.remove(index);
+                    self.changed.push(id);
+                }
+                None => self.added.push(id),
+            }
+        }
+        for id in other.changed {
+            if !self.added.contains(&id) && !self.changed.contains(&id) {
+                self.changed.push(id);
+            }
+        }
+        for id in other.removed {
+            match self.added.iter().position(|added| *added == id) {
+                Some(index) => {
+                    self.added.remove(index);
+                }
+                None => {
+                    self.changed.retain(|changed| *changed != id);
+                    self.removed.push(id);
+                }
+            }
+        }
+    }
+}
+
+impl<P: Prototype> Default for PrototypesChanged<P> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            changed: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
@@ -1,84 +1,257 @@
 use crate::prelude::*;
 use bevy::{
-    ecs::world::CommandQueue,
+    ecs::{system::EntityCommands, world::CommandQueue},
     prelude::*,
-    tasks::{AsyncComputeTaskPool, Task},
+    tasks::AsyncComputeTaskPool,
 };
 
-#[derive(Component, Debug)]
-pub(crate) struct ProtoSpawnTask(pub Task<CommandQueue>);
+/// Builds a prepared prototype on the entity that was spawned for it
+pub(crate) type PreparedBuild = Box<dyn FnOnce(&mut EntityWorldMut) + Send + Sync>;
+
+/// The task preparing a prototype spawned with [`SpawnPrototypeAsyncExt`].
+/// Despawning the entity drops the task, which cancels it
+#[derive(Component)]
+pub(crate) struct ProtoSpawnTask(pub PooledTask<PreparedBuild>);
+
+impl ProtoSpawnTask {
+    fn new<P: Prototype>(proto: P) -> Self {
+        let task = PooledTask::spawn(AsyncComputeTaskPool::get(), async move {
+            let proto = proto.prepare().await;
+            Box::new(move |entity: &mut EntityWorldMut| instantiate(entity, &proto))
+                as PreparedBuild
+        });
+        Self(task)
+    }
+}
 
 /// An extension trait for [`Commands`] [`CommandQueue`] and [`World`]
 /// to allow direct spawning of prototypes
 pub trait SpawnPrototypeExt {
+    /// The spawned entity, [`EntityCommands`] or [`EntityWorldMut`].
+    /// A [`CommandQueue`] has no entity until it's applied
+    type Spawned<'a>
+    where
+        Self: 'a;
+
     /// Spawns the given prototype
-    fn spawn_prototype<P: Prototype>(&mut self, proto: P);
+    fn spawn_prototype<P: Prototype>(&mut self, proto: P) -> Self::Spawned<'_>;
+
+    /// Spawns the given prototype, then inserts `bundle`.
+    /// The bundle is inserted after [`Prototype::build`], so it overrides the prototype's components
+    fn spawn_prototype_with<P: Prototype, B: Bundle>(
+        &mut self,
+        proto: P,
+        bundle: B,
+    ) -> Self::Spawned<'_>;
 }
 
-/// An extension trait for [`Commands`] [`CommandQueue`] and [`World`]
-/// to allow direct spawning of prototypes in an async task
+/// An extension trait for [`Commands`] and [`World`]
+/// to spawn prototypes from the [`PrototypeLibrary`] by [`Id`] or name.
+///
+/// The prototype is looked up when the spawn is applied, if it's missing
+/// the [`MissingPrototypePolicy`] decides what happens
+pub trait SpawnPrototypeIdExt {
+    /// The spawned entity, [`EntityCommands`] or [`EntityWorldMut`]
+    type Spawned<'a>
+    where
+        Self: 'a;
+
+    /// Spawns the prototype `id` from the [`PrototypeLibrary`]
+    fn spawn_prototype_id<P: Prototype>(&mut self, id: Id<P>) -> Self::Spawned<'_>;
+
+    /// Spawns the prototype named `name` from the [`PrototypeLibrary`]
+    fn spawn_prototype_named<P: Prototype>(&mut self, name: &str) -> Self::Spawned<'_> {
+        self.spawn_prototype_id::<P>(Id::from_name(name))
+    }
+}
+
+/// An extension trait for [`Commands`] and [`World`]
+/// to allow spawning of prototypes that are prepared in an async task
 pub trait SpawnPrototypeAsyncExt {
-    /// Spawns the given prototype in an async task
-    fn spawn_prototype_async<P: Prototype>(&mut self, proto: P);
+    /// The spawned entity, [`EntityCommands`] or [`EntityWorldMut`]
+    type Spawned<'a>
+    where
+        Self: 'a;
+
+    /// Spawns an empty entity, and runs [`Prototype::prepare`] in an async task.
+    /// Once it finishes the prototype is built on the entity during [`ProtoSchedule::Spawning`].
+    ///
+    /// Despawning the entity first cancels the task
+    fn spawn_prototype_async<P: Prototype>(&mut self, proto: P) -> Self::Spawned<'_>;
+}
+
+/// An extension trait for [`EntityCommands`] and [`EntityWorldMut`]
+/// to build prototypes on existing entities
+pub trait PrototypeEntityExt {
+    /// Builds the given prototype on the entity, keeping the components it already has.
+    /// The entity's [`FromPrototype`] and [`PrototypeComponents`] are set as if it was spawned from the prototype,
+    /// except that components it already had are tracked as changed, not added,
+    /// so they're kept if the prototype is replaced
+    fn apply_prototype<P: Prototype>(&mut self, proto: P) -> &mut Self;
+
+    /// Builds the prototype `id` from the [`PrototypeLibrary`] on the entity.
+    /// If it's missing the [`MissingPrototypePolicy`] decides what happens.
+    /// See [`PrototypeEntityExt::apply_prototype`]
+    fn apply_prototype_id<P: Prototype>(&mut self, id: Id<P>) -> &mut Self;
+
+    /// Builds the prototype named `name` from the [`PrototypeLibrary`] on the entity.
+    /// See [`PrototypeEntityExt::apply_prototype`]
+    fn apply_prototype_named<P: Prototype>(&mut self, name: &str) -> &mut Self {
+        self.apply_prototype_id::<P>(Id::from_name(name))
+    }
+
+    /// Replaces the entity's prototype with the prototype `id` from the [`PrototypeLibrary`],
+    /// keeping the entity's id. The components the previous prototype added, and its [`FromPrototype`],
+    /// are removed. Other components, such as ones added by gameplay, are kept.
+    ///
+    /// The previous prototype can be of a different type. Sends a [`PrototypeReplaced`] event.
+    /// If the prototype is missing the [`MissingPrototypePolicy`] decides what happens
+    fn replace_prototype<P: Prototype>(&mut self, id: Id<P>) -> &mut Self;
 }
 
 impl<'w, 's> SpawnPrototypeExt for Commands<'w, 's> {
-    fn spawn_prototype<P: Prototype>(&mut self, proto: P) {
-        let mut e = self.spawn_empty();
+    type Spawned<'a>
+        = EntityCommands<'a>
+    where
+        Self: 'a;
+
+    fn spawn_prototype<P: Prototype>(&mut self, proto: P) -> EntityCommands<'_> {
+        let mut entity = self.spawn_empty();
+        entity.apply_prototype(proto);
+        entity
+    }
 
-        #[cfg(feature = "hot_reload")]
-        e.insert(FromPrototype(proto.id()));
+    fn spawn_prototype_with<P: Prototype, B: Bundle>(
+        &mut self,
+        proto: P,
+        bundle: B,
+    ) -> EntityCommands<'_> {
+        let mut entity = self.spawn_prototype(proto);
+        entity.insert(bundle);
+        entity
+    }
+}
+impl<'w, 's> SpawnPrototypeIdExt for Commands<'w, 's> {
+    type Spawned<'a>
+        = EntityCommands<'a>
+    where
+        Self: 'a;
 
-        e.add(move |mut e: EntityWorldMut| proto.build(&mut e));
+    fn spawn_prototype_id<P: Prototype>(&mut self, id: Id<P>) -> EntityCommands<'_> {
+        let mut entity = self.spawn_empty();
+        entity.apply_prototype_id(id);
+        entity
     }
 }
 impl<'w, 's> SpawnPrototypeAsyncExt for Commands<'w, 's> {
-    fn spawn_prototype_async<P: Prototype>(&mut self, proto: P) {
-        let thread_pool = AsyncComputeTaskPool::get();
-        let task = thread_pool.spawn(async move {
-            let mut queue = CommandQueue::default();
-            queue.spawn_prototype(proto);
-            queue
-        });
+    type Spawned<'a>
+        = EntityCommands<'a>
+    where
+        Self: 'a;
 
-        self.spawn_empty()
-            .insert(ProtoSpawnTask(task))
-            .insert(Name::new("Prototype Loading"));
+    fn spawn_prototype_async<P: Prototype>(&mut self, proto: P) -> EntityCommands<'_> {
+        self.spawn(ProtoSpawnTask::new(proto))
     }
 }
 
 impl SpawnPrototypeExt for CommandQueue {
+    type Spawned<'a> = ();
+
     fn spawn_prototype<P: Prototype>(&mut self, proto: P) {
         self.push(move |world: &mut World| {
-            let mut target = world.spawn_empty();
-            proto.build(&mut target);
+            world.spawn_prototype(proto);
+        });
+    }
 
-            #[cfg(feature = "hot_reload")]
-            target.insert(FromPrototype(proto.id()));
+    fn spawn_prototype_with<P: Prototype, B: Bundle>(&mut self, proto: P, bundle: B) {
+        self.push(move |world: &mut World| {
+            world.spawn_prototype_with(proto, bundle);
         });
     }
 }
 
 impl SpawnPrototypeExt for World {
-    fn spawn_prototype<P: Prototype>(&mut self, proto: P) {
-        let mut target = self.spawn_empty();
-        proto.build(&mut target);
+    type Spawned<'a> = EntityWorldMut<'a>;
+
+    fn spawn_prototype<P: Prototype>(&mut self, proto: P) -> EntityWorldMut<'_> {
+        let mut entity = self.spawn_empty();
+        instantiate(&mut entity, &proto);
+        entity
+    }
 
-        #[cfg(feature = "hot_reload")]
-        target.insert(FromPrototype(proto.id()));
+    fn spawn_prototype_with<P: Prototype, B: Bundle>(
+        &mut self,
+        proto: P,
+        bundle: B,
+    ) -> EntityWorldMut<'_> {
+        let mut entity = self.spawn_prototype(proto);
+        entity.insert(bundle);
+        entity
+    }
+}
+impl SpawnPrototypeIdExt for World {
+    type Spawned<'a> = EntityWorldMut<'a>;
+
+    fn spawn_prototype_id<P: Prototype>(&mut self, id: Id<P>) -> EntityWorldMut<'_> {
+        let mut entity = self.spawn_empty();
+        entity.apply_prototype_id(id);
+        entity
     }
 }
 impl SpawnPrototypeAsyncExt for World {
-    fn spawn_prototype_async<P: Prototype>(&mut self, proto: P) {
-        let thread_pool = AsyncComputeTaskPool::get();
-        let task = thread_pool.spawn(async move {
-            let mut queue = CommandQueue::default();
-            queue.spawn_prototype(proto);
-            queue
-        });
+    type Spawned<'a> = EntityWorldMut<'a>;
+
+    fn spawn_prototype_async<P: Prototype>(&mut self, proto: P) -> EntityWorldMut<'_> {
+        self.spawn(ProtoSpawnTask::new(proto))
+    }
+}
+
+impl PrototypeEntityExt for EntityWorldMut<'_> {
+    fn apply_prototype<P: Prototype>(&mut self, proto: P) -> &mut Self {
+        instantiate(self, &proto);
+        self
+    }
+
+    fn apply_prototype_id<P: Prototype>(&mut self, id: Id<P>) -> &mut Self {
+        if let Some(proto) = resolve_prototype(self.world(), id) {
+            instantiate(self, &proto);
+        }
+        self
+    }
+
+    fn replace_prototype<P: Prototype>(&mut self, id: Id<P>) -> &mut Self {
+        let Some(proto) = resolve_prototype(self.world(), id) else {
+            return self;
+        };
+
+        let previous = replace_prototype(self, &proto);
+        let replaced = PrototypeReplaced {
+            entity: self.id(),
+            id,
+            previous_name: previous.as_ref().map(|p| p.name().to_owned()),
+            previous_type: previous.map(|p| p.type_name()),
+        };
+        self.world_scope(|world| world.send_event(replaced));
+        self
+    }
+}
+
+impl PrototypeEntityExt for EntityCommands<'_> {
+    fn apply_prototype<P: Prototype>(&mut self, proto: P) -> &mut Self {
+        self.add(move |mut e: EntityWorldMut| {
+            e.apply_prototype(proto);
+        })
+    }
+
+    fn apply_prototype_id<P: Prototype>(&mut self, id: Id<P>) -> &mut Self {
+        self.add(move |mut e: EntityWorldMut| {
+            e.apply_prototype_id(id);
+        })
+    }
 
-        self.spawn_empty()
-            .insert(ProtoSpawnTask(task))
-            .insert(Name::new("Prototype Loading"));
+    fn replace_prototype<P: Prototype>(&mut self, id: Id<P>) -> &mut Self {
+        self.add(move |mut e: EntityWorldMut| {
+            e.replace_prototype(id);
+        })
     }
 }
@@ -0,0 +1,14 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// A run condition that is true once every manifest registered for `P` has been processed.
+/// See [`ManifestLoader::prototypes_ready`]
+pub fn prototypes_ready<P: Prototype>() -> impl FnMut(Option<Res<ManifestLoader>>) -> bool + Clone {
+    |loader| loader.is_some_and(|loader| loader.prototypes_ready::<P>())
+}
+
+/// A run condition that is true once every registered manifest has been processed.
+/// See [`ManifestLoader::all_prototypes_ready`]
+pub fn all_prototypes_ready() -> impl FnMut(Option<Res<ManifestLoader>>) -> bool + Clone {
+    |loader| loader.is_some_and(|loader| loader.all_prototypes_ready())
+}
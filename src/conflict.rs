@@ -0,0 +1,48 @@
+use crate::prelude::*;
+use bevy::{asset::AssetPath, prelude::*};
+
+/// How the loader resolves two manifest entries that map to the same [`Id`].
+///
+/// Insert this as a resource to change the policy, the default is [`ConflictPolicy::LastWins`].
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictPolicy {
+    /// Keep the prototype that was loaded first.
+    /// If the file it came from is removed, the one loaded after it is used
+    FirstWins,
+    /// Replace the existing prototype with the one loaded last.
+    /// If the file it came from is removed, the one loaded before it is used again
+    #[default]
+    LastWins,
+    /// Reject every prototype in the manifest file that caused the conflict.
+    /// The file keeps its prototypes from the last time it was accepted,
+    /// and is reported with a [`ManifestLoadFailed`](crate::prelude::ManifestLoadFailed)
+    Error,
+}
+
+/// The kind of conflict between two manifest entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictKind {
+    /// Both entries have the same name
+    DuplicateName,
+    /// The entries have different names that hash to the same [`Id`]
+    HashCollision,
+}
+
+/// Sent when a manifest entry maps to an [`Id`] that is already used by another entry
+#[derive(Event, Debug, Clone)]
+pub struct PrototypeConflict<P: Prototype> {
+    /// The id both entries map to
+    pub id: Id<P>,
+    /// Whether this is a duplicate name or a hash collision
+    pub kind: ConflictKind,
+    /// The name of the entry that was already in the library
+    pub existing_name: String,
+    /// The manifest file the existing entry was loaded from
+    pub existing_path: Option<AssetPath<'static>>,
+    /// The name of the entry that was being loaded
+    pub incoming_name: String,
+    /// The manifest file the incoming entry was loaded from
+    pub incoming_path: Option<AssetPath<'static>>,
+    /// The policy that was used to resolve the conflict
+    pub policy: ConflictPolicy,
+}
@@ -0,0 +1,121 @@
+use crate::load_report::ErrorLocations;
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use std::{fmt::Display, marker::PhantomData};
+
+/// The error returned by the manifest loaders
+#[derive(Debug)]
+pub(crate) enum ManifestReadError<E> {
+    /// The file couldn't be read
+    Io(std::io::Error),
+    /// The file couldn't be parsed
+    Parse(E),
+}
+
+impl<E: Display> Display for ManifestReadError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestReadError::Io(error) => write!(f, "Could not read the file: {error}"),
+            ManifestReadError::Parse(error) => write!(f, "Could not parse the manifest: {error}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for ManifestReadError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ManifestReadError::Io(error) => Some(error),
+            ManifestReadError::Parse(error) => Some(error),
+        }
+    }
+}
+
+/// A format whose parse errors have a line and column
+pub(crate) trait LocatedFormat: Send + Sync + 'static {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    fn parse<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, Self::Error>;
+
+    /// The line and column of the error, if it has one
+    fn location(error: &Self::Error) -> Option<(usize, usize)>;
+}
+
+/// Loads assets of type `T` in the format `F`, recording where parse errors are in [`ErrorLocations`]
+pub(crate) struct LocatedLoader<T, F> {
+    extensions: &'static [&'static str],
+    locations: ErrorLocations,
+    _marker: PhantomData<fn() -> (T, F)>,
+}
+
+impl<T, F> LocatedLoader<T, F> {
+    pub(crate) fn new(extensions: &'static [&'static str], locations: ErrorLocations) -> Self {
+        Self {
+            extensions,
+            locations,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Asset + for<'de> Deserialize<'de>, F: LocatedFormat> AssetLoader for LocatedLoader<T, F> {
+    type Asset = T;
+    type Settings = ();
+    type Error = ManifestReadError<F::Error>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<T, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(ManifestReadError::Io)?;
+        let result = F::parse(&bytes);
+        let location = result.as_ref().err().and_then(F::location);
+        self.locations.set(load_context.asset_path(), location);
+        result.map_err(ManifestReadError::Parse)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
+
+#[cfg(feature = "ron")]
+pub(crate) struct Ron;
+
+#[cfg(feature = "ron")]
+impl LocatedFormat for Ron {
+    type Error = ron::error::SpannedError;
+
+    fn parse<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, Self::Error> {
+        ron::de::from_bytes(bytes)
+    }
+
+    fn location(error: &Self::Error) -> Option<(usize, usize)> {
+        Some((error.position.line, error.position.col))
+    }
+}
+
+#[cfg(feature = "json")]
+pub(crate) struct Json;
+
+#[cfg(feature = "json")]
+impl LocatedFormat for Json {
+    type Error = serde_json::Error;
+
+    fn parse<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, Self::Error> {
+        serde_json::from_slice(bytes)
+    }
+
+    fn location(error: &Self::Error) -> Option<(usize, usize)> {
+        // Io errors don't have a location, and report line 0
+        (error.line() > 0).then(|| (error.line(), error.column()))
+    }
+}
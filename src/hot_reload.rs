@@ -1,11 +1,14 @@
-use crate::prelude::*;
-use bevy::{ecs::system::SystemId, prelude::*, utils::HashMap};
+use crate::{prelude::*, prototype_components::build_tracked};
+use bevy::{
+    ecs::{
+        component::ComponentId,
+        system::{EntityCommands, SystemId},
+    },
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 use std::any::TypeId;
 
-#[cfg(feature = "hot_reload")]
-#[derive(Debug, Clone, Component, PartialEq, Eq)]
-pub struct FromPrototype<P: Prototype>(pub Id<P>);
-
 #[derive(Resource)]
 pub(crate) struct RebuildSystems(pub HashMap<TypeId, SystemId>);
 
@@ -21,17 +24,120 @@ impl Default for RebuildSystems {
     }
 }
 
+/// Updates the entities whose prototype was added, changed or removed,
+/// as set by the [`ReloadSettings`] of the prototype
 fn rebuild<P: Prototype>(
     mut commands: Commands,
-    query: Query<(Entity, &FromPrototype<P>)>,
-    protos: ResMut<PrototypeLibrary<P>>,
+    mut changes: EventReader<PrototypesChanged<P>>,
+    query: Query<(Entity, &FromPrototype<P>, Has<OrphanedPrototype>)>,
+    protos: Res<PrototypeLibrary<P>>,
+    settings: Res<ReloadSettings<P>>,
+    mut reloaded: EventWriter<EntitiesReloaded<P>>,
+    mut orphaned: EventWriter<EntitiesOrphaned<P>>,
 ) {
-    for (e, proto) in query.iter() {
-        if let Some(proto) = protos.get(&proto.0) {
-            let mut target = commands.entity(e);
-            target.add(move |mut e: EntityWorldMut| proto.build(&mut e));
+    let changed: HashSet<Id<P>> = changes.read().flat_map(|c| c.ids().copied()).collect();
+    if changed.is_empty() {
+        return;
+    }
+
+    let policy = settings.policy;
+    let mut entities = Vec::new();
+    let mut orphans = Vec::new();
+    for (e, from, is_orphaned) in query
+        .iter()
+        .filter(|(_, from, _)| changed.contains(&from.0))
+    {
+        let mut target = commands.entity(e);
+        let Some(proto) = protos.get(&from.0) else {
+            orphans.push((e, from.0));
+            match &settings.orphans {
+                OrphanPolicy::Despawn => target.despawn_recursive(),
+                OrphanPolicy::Tag => {
+                    target.insert(OrphanedPrototype);
+                }
+                OrphanPolicy::Fallback(placeholder) => match protos.get(placeholder) {
+                    Some(placeholder) => {
+                        target.insert(FromPrototype(placeholder.id()));
+                        reload(&mut target, placeholder, &settings);
+                        if is_orphaned {
+                            target.remove::<OrphanedPrototype>();
+                        }
+                    }
+                    None => {
+                        warn!(
+                            "Placeholder prototype {} not found for orphaned entity {:?}",
+                            placeholder.describe(),
+                            e
+                        );
+                        target.insert(OrphanedPrototype);
+                    }
+                },
+            }
+            continue;
+        };
+
+        entities.push((e, from.0));
+        reload(&mut target, proto, &settings);
+        if is_orphaned {
+            target.remove::<OrphanedPrototype>();
         }
     }
+
+    if !entities.is_empty() {
+        reloaded.send(EntitiesReloaded { entities, policy });
+    }
+    if !orphans.is_empty() {
+        orphaned.send(EntitiesOrphaned {
+            entities: orphans,
+            policy: settings.orphans.clone(),
+        });
+    }
+}
+
+/// Applies the prototype to an entity it was spawned on, using the reload settings
+fn reload<P: Prototype>(target: &mut EntityCommands, proto: P, settings: &ReloadSettings<P>) {
+    let remove_stale = settings.remove_stale_components;
+    match settings.policy {
+        ReloadPolicy::Rebuild => {
+            target.add(move |mut e: EntityWorldMut| {
+                build_tracked(&mut e, &proto, remove_stale, |e| proto.rebuild(e));
+            });
+        }
+        ReloadPolicy::Build => {
+            target.add(move |mut e: EntityWorldMut| {
+                build_tracked(&mut e, &proto, remove_stale, |e| proto.build(e));
+            });
+        }
+        ReloadPolicy::Respawn => {
+            target.add(move |mut e: EntityWorldMut| {
+                e.despawn_descendants();
+                remove_added_components(&mut e);
+                build_tracked(&mut e, &proto, false, |e| proto.build(e));
+            });
+        }
+        ReloadPolicy::EventOnly => (),
+    }
+}
+
+/// Removes the components the entity's prototype added, except for its transforms,
+/// so it's respawned in place
+fn remove_added_components(target: &mut EntityWorldMut) {
+    let Some(tracking) = target.get::<PrototypeComponents>() else {
+        return;
+    };
+
+    let world = target.world();
+    let kept = [
+        world.component_id::<Transform>(),
+        world.component_id::<GlobalTransform>(),
+    ];
+    let added: Vec<ComponentId> = tracking
+        .added()
+        .filter(|id| !kept.contains(&Some(*id)))
+        .collect();
+    for id in added {
+        target.remove_by_id(id);
+    }
 }
 
 pub fn register_rebuild_system<P: Prototype>(world: &mut World) {
@@ -6,12 +6,21 @@
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use std::{
+    any::TypeId,
+    sync::{OnceLock, RwLock},
+};
 use std::{
     fmt::{Debug, Display},
     hash::Hash,
     marker::PhantomData,
 };
 
+/// Names of every [`Id`] created from a manifest, by id type and value, for [`Id::debug_name`].
+#[cfg(debug_assertions)]
+static NAMES: OnceLock<RwLock<bevy::utils::HashMap<(TypeId, u64), String>>> = OnceLock::new();
+
 /// The unique identifier of type `T`.
 ///
 /// These are constructed by hashing object names via [`Id::from_name`],
@@ -35,12 +44,18 @@ pub struct Id<T> {
     _phantom: PhantomData<T>,
 }
 
-/// A constant used in the hashing algorithm of the IDs.
+/// The offset basis of the 64-bit FNV-1a hash used by [`Id::from_name`].
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// The prime of the 64-bit FNV-1a hash used by [`Id::from_name`].
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A constant used in the legacy hashing algorithm of the IDs.
 ///
 /// This should be a positive prime number, roughly equal to the number of characters in the input alphabet.
 const HASH_P: u64 = 53;
 
-/// A constant used in the hashing algorithm of the IDs.
+/// A constant used in the legacy hashing algorithm of the IDs.
 ///
 /// This should be a large prime number as it is used for modulo operations.
 /// Larger numbers have a lower chance of a hash collision.
@@ -48,8 +63,52 @@ const HASH_M: u64 = 1_000_000_009;
 
 impl<T> Id<T> {
     /// Creates a new ID from a string.
+    ///
+    /// This uses the 64-bit FNV-1a hash, unless the `legacy_id_hash` feature is enabled,
+    /// in which case it is equivalent to [`Id::from_name_legacy`].
     #[must_use]
     pub const fn from_name(name: &str) -> Self {
+        #[cfg(feature = "legacy_id_hash")]
+        {
+            Self::from_name_legacy(name)
+        }
+        #[cfg(not(feature = "legacy_id_hash"))]
+        {
+            Self::from_name_fnv1a(name)
+        }
+    }
+
+    /// Creates a new ID from a string using the 64-bit FNV-1a hash.
+    ///
+    /// This is the default algorithm used by [`Id::from_name`].
+    #[must_use]
+    pub const fn from_name_fnv1a(name: &str) -> Self {
+        let mut value = FNV_OFFSET_BASIS;
+
+        // BLOCKED: this should just be a for loop over name.as_bytes, but they aren't allowed in const fns yet.
+        // see <https://github.com/rust-lang/rust/issues/87575> for more information
+        let byte_slice = name.as_bytes();
+        let mut byte_index = 0;
+
+        while byte_index < byte_slice.len() {
+            value ^= byte_slice[byte_index] as u64;
+            value = value.wrapping_mul(FNV_PRIME);
+            byte_index += 1;
+        }
+
+        Id {
+            value,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Creates a new ID from a string using the polynomial hash from before 64-bit hashing was introduced.
+    ///
+    /// This only has around 30 bits of entropy, so it is prone to collisions.
+    /// It is kept so that raw [`Id`] values stored by older versions (e.g. in save files)
+    /// can be matched against their names and migrated.
+    #[must_use]
+    pub const fn from_name_legacy(name: &str) -> Self {
         // Algorithm adopted from <https://cp-algorithms.com/string/string-hashing.htl>
         let mut value = 0;
         let mut p_pow = 1;
@@ -100,9 +159,65 @@ impl<T> Id<T> {
     }
 }
 
-impl<T> Debug for Id<T> {
+impl<T: 'static> Id<T> {
+    /// Returns the name this ID was created from, if it is known.
+    ///
+    /// Names are only tracked in debug builds, for IDs of prototypes loaded from manifests
+    /// and [`IdRef`](crate::prelude::IdRef)s. This is meant for logging,
+    /// use [`PrototypeLibrary::name`](crate::prelude::PrototypeLibrary::name) to get the name of a prototype.
+    #[must_use]
+    pub fn debug_name(&self) -> Option<String> {
+        #[cfg(debug_assertions)]
+        {
+            let names = NAMES.get()?.read().ok()?;
+            names.get(&(TypeId::of::<T>(), self.value)).cloned()
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            None
+        }
+    }
+
+    /// Displays the ID with its [`Id::debug_name`] if it is known, as `"iron_sword" (1234)`,
+    /// or as its raw value otherwise
+    pub fn describe(&self) -> impl Display {
+        Described {
+            value: self.value,
+            name: self.debug_name(),
+        }
+    }
+
+    /// Records the name of this ID for [`Id::debug_name`]
+    pub(crate) fn record_name(&self, _name: &str) {
+        #[cfg(debug_assertions)]
+        if let Ok(mut names) = NAMES.get_or_init(Default::default).write() {
+            names.insert((TypeId::of::<T>(), self.value), _name.to_string());
+        }
+    }
+}
+
+/// An [`Id`] displayed with its name, see [`Id::describe`]
+struct Described {
+    value: u64,
+    name: Option<String>,
+}
+
+impl Display for Described {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Id").field("value", &self.value).finish()
+        match &self.name {
+            Some(name) => write!(f, "{name:?} ({})", self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+/// Prints the ID as `Id("iron_sword")` if its [`Id::debug_name`] is known, or as `Id(0x1234)` otherwise
+impl<T: 'static> Debug for Id<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.debug_name() {
+            Some(name) => f.debug_tuple("Id").field(&name).finish(),
+            None => write!(f, "Id({:#x})", self.value),
+        }
     }
 }
 
@@ -145,3 +260,105 @@ impl<T> Clone for Id<T> {
 }
 
 impl<T> Copy for Id<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Sword;
+    struct Shield;
+
+    impl crate::prelude::Prototype for Sword {
+        fn name(&self) -> String {
+            "sword".to_string()
+        }
+
+        fn build(&self, _target: &mut EntityWorldMut) {}
+
+        fn content_hash(&self) -> u64 {
+            0
+        }
+    }
+
+    #[test]
+    fn hashes_names_with_fnv1a() {
+        assert_eq!(
+            Id::<Sword>::from_name_fnv1a("").raw(),
+            0xcbf2_9ce4_8422_2325
+        );
+        assert_eq!(
+            Id::<Sword>::from_name_fnv1a("a").raw(),
+            0xaf63_dc4c_8601_ec8c
+        );
+        assert_eq!(
+            Id::<Sword>::from_name_fnv1a("foobar").raw(),
+            0x8594_4171_f739_67e8
+        );
+    }
+
+    #[test]
+    fn legacy_hash_matches_the_old_from_name() {
+        assert_eq!(Id::<Sword>::from_name_legacy("").raw(), 0);
+        assert_eq!(Id::<Sword>::from_name_legacy("a").raw(), 98);
+        assert_eq!(
+            Id::<Sword>::from_name_legacy("iron_sword").raw(),
+            760_196_352
+        );
+    }
+
+    #[test]
+    fn from_name_uses_the_hash_picked_by_features() {
+        let expected = if cfg!(feature = "legacy_id_hash") {
+            Id::from_name_legacy("iron_sword")
+        } else {
+            Id::from_name_fnv1a("iron_sword")
+        };
+        assert_eq!(Id::<Sword>::from_name("iron_sword"), expected);
+    }
+
+    #[test]
+    fn displays_the_raw_value() {
+        let id = Id::<Sword>::from_name("iron");
+        id.record_name("iron");
+
+        assert_eq!(id.to_string(), id.raw().to_string());
+    }
+
+    #[test]
+    fn debugs_the_name_or_the_hex_value() {
+        let named = Id::<Sword>::from_name("steel");
+        named.record_name("steel");
+        let unnamed = Id::<Sword>::from_raw(0xbeef);
+
+        if cfg!(debug_assertions) {
+            assert_eq!(format!("{named:?}"), "Id(\"steel\")");
+        } else {
+            assert_eq!(format!("{named:?}"), format!("Id({:#x})", named.raw()));
+        }
+        assert_eq!(format!("{unnamed:?}"), "Id(0xbeef)");
+        assert_eq!(
+            format!(
+                "{:?}",
+                crate::prelude::FromPrototype(Id::<Sword>::from_raw(0xbeef))
+            ),
+            "FromPrototype(Id(0xbeef))"
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn names_are_tracked_per_type() {
+        let sword = Id::<Sword>::from_name("oak");
+        sword.record_name("oak");
+
+        assert_eq!(sword.debug_name().as_deref(), Some("oak"));
+        assert_eq!(
+            sword.describe().to_string(),
+            format!("\"oak\" ({})", sword.raw())
+        );
+        let shield = Id::<Shield>::from_raw(sword.raw());
+        assert_eq!(shield.debug_name(), None);
+        assert_eq!(shield.describe().to_string(), shield.raw().to_string());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::prelude::*;
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+/// A reference to another prototype by name, for use in manifests.
+///
+/// This deserializes from the name of the prototype (e.g. `"iron_sword"`),
+/// which is hashed with [`Id::from_name`], so manifests can reference each other
+/// without magic numbers. Raw [`Id`] values are also accepted.
+///
+/// When serialized, the name is written back out if it is known, otherwise the raw value is used.
+pub struct IdRef<T> {
+    id: Id<T>,
+    name: Option<String>,
+}
+
+impl<T: 'static> IdRef<T> {
+    /// Creates a new reference from the name of a prototype
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        let id = Id::from_name(&name);
+        id.record_name(&name);
+        Self {
+            id,
+            name: Some(name),
+        }
+    }
+
+    /// Returns the id of the referenced prototype
+    #[must_use]
+    pub fn id(&self) -> Id<T> {
+        self.id
+    }
+
+    /// Returns the name of the referenced prototype, if it is known
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl<T> From<Id<T>> for IdRef<T> {
+    fn from(id: Id<T>) -> Self {
+        Self { id, name: None }
+    }
+}
+
+impl<T> From<IdRef<T>> for Id<T> {
+    fn from(id_ref: IdRef<T>) -> Self {
+        id_ref.id
+    }
+}
+
+impl<T> From<&IdRef<T>> for Id<T> {
+    fn from(id_ref: &IdRef<T>) -> Self {
+        id_ref.id
+    }
+}
+
+impl<T> Serialize for IdRef<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.name {
+            Some(name) => serializer.serialize_str(name),
+            None => serializer.serialize_u64(self.id.raw()),
+        }
+    }
+}
+
+impl<'de, T: 'static> Deserialize<'de> for IdRef<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(IdRefVisitor(PhantomData))
+    }
+}
+
+struct IdRefVisitor<T>(PhantomData<T>);
+
+impl<'de, T: 'static> Visitor<'de> for IdRefVisitor<T> {
+    type Value = IdRef<T>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "the name of a prototype, or a raw id")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(IdRef::new(v))
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Id::from_raw(v).into())
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        let v = u64::try_from(v).map_err(|_| E::custom("raw ids can't be negative"))?;
+        self.visit_u64(v)
+    }
+}
+
+impl<T: 'static> Debug for IdRef<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => f.debug_tuple("IdRef").field(name).finish(),
+            None => f.debug_tuple("IdRef").field(&self.id).finish(),
+        }
+    }
+}
+
+impl<T> Display for IdRef<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+impl<T> Clone for IdRef<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            name: self.name.clone(),
+        }
+    }
+}
+
+impl<T> PartialEq for IdRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for IdRef<T> {}
+
+impl<T> Hash for IdRef<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sword;
+
+    #[test]
+    fn round_trips_names_through_ron() {
+        let named = IdRef::<Sword>::new("iron_sword");
+        let text = ron::to_string(&named).unwrap();
+        assert_eq!(text, "\"iron_sword\"");
+
+        let read: IdRef<Sword> = ron::from_str(&text).unwrap();
+        assert_eq!(read.id(), named.id());
+        assert_eq!(read.name(), Some("iron_sword"));
+    }
+
+    #[test]
+    fn round_trips_raw_ids_through_ron() {
+        let raw = IdRef::<Sword>::from(Id::from_raw(42));
+        let text = ron::to_string(&raw).unwrap();
+        assert_eq!(text, "42");
+
+        let read: IdRef<Sword> = ron::from_str(&text).unwrap();
+        assert_eq!(read.id(), Id::from_raw(42));
+        assert_eq!(read.name(), None);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let refs = vec![
+            IdRef::<Sword>::new("iron_sword"),
+            IdRef::from(Id::from_raw(42)),
+        ];
+        let text = serde_json::to_string(&refs).unwrap();
+        assert_eq!(text, "[\"iron_sword\",42]");
+
+        let read: Vec<IdRef<Sword>> = serde_json::from_str(&text).unwrap();
+        assert_eq!(read, refs);
+        assert_eq!(read[0].name(), Some("iron_sword"));
+        assert_eq!(read[1].name(), None);
+    }
+}
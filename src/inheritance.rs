@@ -0,0 +1,171 @@
+use crate::{prelude::*, raw_value::RawValue};
+use bevy::{
+    asset::UntypedAssetId,
+    utils::{HashMap, HashSet},
+};
+use std::fmt::Display;
+
+/// The field naming the entry a manifest entry inherits from
+const PARENT_FIELD: &str = "parent";
+/// The field marking a manifest entry as a template that never becomes a prototype
+const ABSTRACT_FIELD: &str = "abstract";
+/// The field used to find parents by name
+const NAME_FIELD: &str = "name";
+
+/// An error resolving a raw manifest entry
+#[derive(Debug, Clone)]
+pub(crate) enum EntryError {
+    /// The parent doesn't exist in any loaded file of the same type
+    MissingParent { parent: String },
+    /// More than one entry has the parent's name, so it's unclear which to inherit from
+    AmbiguousParent { parent: String, count: usize },
+    /// The parent chain loops back on itself
+    Cycle { chain: Vec<String> },
+    /// The entry couldn't be deserialized into the manifest
+    Deserialize { message: String },
+}
+
+impl Display for EntryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntryError::MissingParent { parent } => {
+                write!(f, "inherits from missing parent {parent:?}")
+            }
+            EntryError::AmbiguousParent { parent, count } => {
+                write!(
+                    f,
+                    "inherits from {parent:?}, which is defined by {count} entries"
+                )
+            }
+            EntryError::Cycle { chain } => {
+                write!(f, "has a cyclic parent chain: {}", chain.join(" -> "))
+            }
+            EntryError::Deserialize { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+/// The entries of a manifest file, labelled with their name or index
+pub(crate) type ResolvedEntries<M> = Vec<(String, Result<M, EntryError>)>;
+
+/// Resolves the raw entries of the files in `resolve`, deserializing each entry on its own.
+/// Entries are labelled with their name, or their index if they don't have one.
+///
+/// If the manifest supports inheritance each entry is merged over its chain of parents first,
+/// which are looked up in every file. Abstract entries are only used as parents, so they are not returned.
+/// Files are returned in the order of `files`
+pub(crate) fn resolve_entries<M: Manifest>(
+    files: &[(UntypedAssetId, &[RawValue])],
+    resolve: &[UntypedAssetId],
+) -> Vec<(UntypedAssetId, ResolvedEntries<M>)> {
+    let mut index: HashMap<&str, Vec<&RawValue>> = HashMap::new();
+    for entry in files.iter().flat_map(|(_, entries)| entries.iter()) {
+        if let Some(name) = entry_name(entry) {
+            index.entry(name).or_default().push(entry);
+        }
+    }
+
+    files
+        .iter()
+        .filter(|(source, _)| resolve.contains(source))
+        .map(|(source, entries)| {
+            let resolved = entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| !(M::INHERITANCE && is_abstract(entry)))
+                .map(|(i, entry)| {
+                    let label = entry_name(entry).map_or_else(|| format!("#{i}"), str::to_string);
+                    let result = if M::INHERITANCE {
+                        resolve_entry(entry, &index)
+                    } else {
+                        deserialize_entry(entry.clone())
+                    };
+                    (label, result)
+                })
+                .collect();
+            (*source, resolved)
+        })
+        .collect()
+}
+
+/// Returns the names of the files' entries that inherit from one of `names`, and the files they're in
+pub(crate) fn inheriting_from<'a>(
+    files: &'a [(UntypedAssetId, &[RawValue])],
+    names: &'a HashSet<String>,
+) -> impl Iterator<Item = (UntypedAssetId, Option<&'a str>)> + 'a {
+    files.iter().flat_map(move |(source, entries)| {
+        entries
+            .iter()
+            .filter(|entry| parent_name(entry).is_some_and(|parent| names.contains(parent)))
+            .map(|entry| (*source, entry_name(entry)))
+    })
+}
+
+/// Returns the names of the entries
+pub(crate) fn entry_names(entries: &[RawValue]) -> impl Iterator<Item = &str> {
+    entries.iter().filter_map(entry_name)
+}
+
+fn entry_name(entry: &RawValue) -> Option<&str> {
+    entry.get(NAME_FIELD)?.as_str()
+}
+
+fn parent_name(entry: &RawValue) -> Option<&str> {
+    entry.get(PARENT_FIELD)?.as_str()
+}
+
+fn is_abstract(entry: &RawValue) -> bool {
+    entry
+        .get(ABSTRACT_FIELD)
+        .and_then(RawValue::as_bool)
+        .unwrap_or(false)
+}
+
+fn resolve_entry<M: Manifest>(
+    entry: &RawValue,
+    index: &HashMap<&str, Vec<&RawValue>>,
+) -> Result<M, EntryError> {
+    let mut chain = vec![entry];
+    let mut names: Vec<String> = entry_name(entry).map(str::to_string).into_iter().collect();
+    let mut current = entry;
+    while let Some(parent_name) = parent_name(current) {
+        if names.iter().any(|n| n == parent_name) {
+            names.push(parent_name.to_string());
+            return Err(EntryError::Cycle { chain: names });
+        }
+
+        let parent = match index.get(parent_name).map(Vec::as_slice) {
+            Some([parent]) => *parent,
+            Some(parents) if parents.len() > 1 => {
+                return Err(EntryError::AmbiguousParent {
+                    parent: parent_name.to_string(),
+                    count: parents.len(),
+                })
+            }
+            _ => {
+                return Err(EntryError::MissingParent {
+                    parent: parent_name.to_string(),
+                })
+            }
+        };
+
+        names.push(parent_name.to_string());
+        chain.push(parent);
+        current = parent;
+    }
+
+    let mut merged = RawValue::Unit;
+    for raw in chain.into_iter().rev() {
+        merged = merged.merge(raw.clone());
+    }
+    merged.remove(PARENT_FIELD);
+    merged.remove(ABSTRACT_FIELD);
+
+    deserialize_entry(merged)
+}
+
+fn deserialize_entry<M: Manifest>(entry: RawValue) -> Result<M, EntryError> {
+    M::deserialize(entry).map_err(|e| EntryError::Deserialize {
+        message: e.to_string(),
+    })
+}
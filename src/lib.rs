@@ -1,41 +1,80 @@
 //! An opinionated library for loading and spawning prototypes
+mod batch;
+mod changes;
 mod commands_ext;
+mod conditions;
+mod conflict;
+#[cfg(any(feature = "ron", feature = "json"))]
+mod format_loaders;
 #[cfg(feature = "hot_reload")]
 mod hot_reload;
 mod id;
+mod id_ref;
+mod inheritance;
+mod load_report;
 mod manifest_collection;
 mod manifest_format;
 mod manifest_loader;
 mod manifest_trait;
+mod missing;
+mod pooled_task;
+mod progress;
 mod proto_plugin;
+mod prototype_components;
 mod prototype_library;
 mod prototype_trait;
+mod raw_value;
+mod references;
 mod register;
+mod reload;
 mod schedule;
 mod systems;
+mod templates;
 
 #[doc(hidden)]
 pub mod prelude {
     pub(crate) use crate::{
         commands_ext::ProtoSpawnTask,
         manifest_collection::ManifestCollection,
-        manifest_loader::ManifestLoader,
         manifest_trait::AccessManifestFormat,
-        systems::{handle_async_spawn, load, track_asset},
+        missing::resolve_prototype,
+        pooled_task::PooledTask,
+        prototype_components::{build_prototype, replace_prototype},
+        prototype_library::SourceConflict,
+        references::{register_reference_validator, validate_references},
+        systems::{handle_async_spawn, load, track_asset, track_folders},
+        templates::{instantiate, update_templates, ComponentTemplate},
     };
 
     #[cfg(feature = "hot_reload")]
     pub(crate) use crate::hot_reload::*;
 
     pub use crate::{
-        commands_ext::{SpawnPrototypeAsyncExt, SpawnPrototypeExt},
+        batch::SpawnPrototypeBatchExt,
+        changes::PrototypesChanged,
+        commands_ext::{
+            PrototypeEntityExt, SpawnPrototypeAsyncExt, SpawnPrototypeExt, SpawnPrototypeIdExt,
+        },
+        conditions::{all_prototypes_ready, prototypes_ready},
+        conflict::{ConflictKind, ConflictPolicy, PrototypeConflict},
         id::Id,
+        id_ref::IdRef,
+        load_report::{ManifestEntryError, ManifestLoadFailed, ManifestLoadReport},
         manifest_format::ManifestFormat,
-        manifest_trait::Manifest,
+        manifest_loader::{ManifestLoader, ManifestState},
+        manifest_trait::{Manifest, ReifyError},
+        missing::MissingPrototypePolicy,
+        progress::ProtoProgress,
         proto_plugin::ProtoPlugin,
+        prototype_components::{FromPrototype, PrototypeComponents, PrototypeReplaced},
         prototype_library::PrototypeLibrary,
         prototype_trait::Prototype,
+        references::{DanglingReference, PrototypeReference},
         register::RegisterPrototype,
+        reload::{
+            EntitiesOrphaned, EntitiesReloaded, OrphanPolicy, OrphanedPrototype, ReloadPolicy,
+            ReloadSettings,
+        },
         schedule::ProtoSchedule,
     };
 }
@@ -0,0 +1,131 @@
+use crate::prelude::*;
+use bevy::{asset::AssetPath, prelude::*, utils::HashMap};
+use std::sync::{Arc, Mutex};
+
+/// Sent when an entry in a manifest file can't be turned into a prototype,
+/// either because it is malformed or because [`Manifest::try_reify`](crate::prelude::Manifest::try_reify) failed
+#[derive(Event, Debug, Clone)]
+pub struct ManifestEntryError {
+    /// The type name of the manifest
+    pub manifest: &'static str,
+    /// The manifest file the entry is in
+    pub path: Option<AssetPath<'static>>,
+    /// The name of the entry, or its index (e.g. `#3`) if the name isn't known
+    pub entry: String,
+    /// What went wrong
+    pub message: String,
+}
+
+/// Sent when a manifest file fails to load, usually because it couldn't be parsed,
+/// or when its entries are rejected because some are invalid
+#[derive(Event, Debug, Clone)]
+pub struct ManifestLoadFailed {
+    /// The type name of the manifest
+    pub manifest: &'static str,
+    /// The manifest file that failed to load
+    pub path: AssetPath<'static>,
+    /// The format the file was being read as
+    pub format: ManifestFormat,
+    /// The line of the error, if the format reports it
+    pub line: Option<usize>,
+    /// The column of the error, if the format reports it
+    pub column: Option<usize>,
+    /// The underlying error message
+    pub message: String,
+}
+
+impl ManifestLoadFailed {
+    pub(crate) fn new<M: Manifest>(
+        path: AssetPath<'static>,
+        message: String,
+        location: Option<(usize, usize)>,
+    ) -> Self {
+        let (line, column) = location.unzip();
+        Self {
+            manifest: std::any::type_name::<M>(),
+            path,
+            format: M::FORMAT,
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+/// The line and column of the parse error of every manifest file that failed to parse,
+/// shared between the [`AssetLoader`](bevy::asset::AssetLoader)s of formats that report them and the [`ManifestLoader`](crate::prelude::ManifestLoader)
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ErrorLocations(Arc<Mutex<HashMap<AssetPath<'static>, (usize, usize)>>>);
+
+impl ErrorLocations {
+    /// Takes the location of the parse error in the file, if its format reported one
+    pub(crate) fn take(&self, path: &AssetPath<'static>) -> Option<(usize, usize)> {
+        self.0.lock().ok()?.remove(path)
+    }
+
+    /// Records the location of the file's parse error, or that it parsed
+    #[cfg(any(feature = "ron", feature = "json"))]
+    pub(crate) fn set(&self, path: &AssetPath<'static>, location: Option<(usize, usize)>) {
+        let Ok(mut locations) = self.0.lock() else {
+            return;
+        };
+        match location {
+            Some(location) => locations.insert(path.clone_owned(), location),
+            None => locations.remove(path),
+        };
+    }
+}
+
+/// Every entry error and load failure from the most recent load of each manifest file.
+/// These are replaced whenever the file is reloaded.
+#[derive(Resource, Debug, Default)]
+pub struct ManifestLoadReport {
+    errors: Vec<ManifestEntryError>,
+    failures: Vec<ManifestLoadFailed>,
+}
+
+impl ManifestLoadReport {
+    /// Returns true if there are no entry errors or load failures
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty() && self.failures.is_empty()
+    }
+
+    /// Returns every manifest file that failed to load
+    pub fn failures(&self) -> &[ManifestLoadFailed] {
+        &self.failures
+    }
+
+    /// Returns every entry error
+    pub fn errors(&self) -> &[ManifestEntryError] {
+        &self.errors
+    }
+
+    /// Returns the entry errors for one manifest file
+    pub fn errors_for<'a>(
+        &'a self,
+        path: &'a AssetPath,
+    ) -> impl Iterator<Item = &'a ManifestEntryError> {
+        self.errors
+            .iter()
+            .filter(move |e| e.path.as_ref() == Some(path))
+    }
+
+    /// Replaces the errors for the given manifest files, which have been processed
+    pub(crate) fn replace(
+        &mut self,
+        paths: &[Option<AssetPath<'static>>],
+        errors: impl IntoIterator<Item = ManifestEntryError>,
+    ) {
+        self.errors.retain(|e| !paths.contains(&e.path));
+        self.failures
+            .retain(|f| !paths.iter().flatten().any(|p| *p == f.path));
+        self.errors.extend(errors);
+    }
+
+    /// Records that a manifest file failed to load
+    pub(crate) fn add_failure(&mut self, failure: ManifestLoadFailed) {
+        self.failures.retain(|f| f.path != failure.path);
+        self.failures.push(failure);
+    }
+}
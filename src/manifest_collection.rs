@@ -1,13 +1,44 @@
-use crate::prelude::*;
+use crate::{prelude::*, raw_value::RawValue};
 use bevy::prelude::*;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 
-#[derive(Deserialize, Clone, Asset, TypePath, Debug)]
+#[derive(Clone, Asset, TypePath, Debug)]
 pub struct ManifestCollection<M>
 where
     M: Asset + Clone,
 {
     pub prototypes: Vec<M>,
+    /// The unresolved entries of a manifest that supports inheritance or is lenient,
+    /// in which case `prototypes` is empty
+    pub raw: Vec<RawValue>,
+}
+
+impl<'de, M> Deserialize<'de> for ManifestCollection<M>
+where
+    M: Manifest,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(bound = "T: Deserialize<'de>")]
+        #[serde(rename = "ManifestCollection")]
+        struct Entries<T> {
+            prototypes: Vec<T>,
+        }
+
+        if M::INHERITANCE || M::LENIENT {
+            let entries = Entries::<RawValue>::deserialize(deserializer)?;
+            Ok(Self {
+                prototypes: Vec::new(),
+                raw: entries.prototypes,
+            })
+        } else {
+            let entries = Entries::<M>::deserialize(deserializer)?;
+            Ok(Self {
+                prototypes: entries.prototypes,
+                raw: Vec::new(),
+            })
+        }
+    }
 }
 
 impl<M> AccessManifestFormat for ManifestCollection<M>
@@ -26,6 +57,7 @@ where
     fn default() -> Self {
         Self {
             prototypes: Default::default(),
+            raw: Default::default(),
         }
     }
 }
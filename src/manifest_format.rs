@@ -2,6 +2,7 @@
 ///
 /// All of the corresponding features are off by default, and must be enabled with feature flags.
 /// Check the `Cargo.toml` file for the list of available features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ManifestFormat {
     #[cfg(feature = "ron")]
     /// A Rust-specific configuration format that is easy for both humans and machines to read and write.
@@ -29,3 +30,29 @@ pub enum ManifestFormat {
     /// If this is selected, you will need to create and register your own [`bevy::asset::AssetLoader`] trait for the [`Manifest`] asset type.
     Custom,
 }
+
+impl ManifestFormat {
+    /// The file extensions used by this format, used to filter files when
+    /// loading a folder of manifests.
+    ///
+    /// [`ManifestFormat::Custom`] has no known extensions, so every file in the folder is loaded.
+    pub const fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            #[cfg(feature = "ron")]
+            ManifestFormat::Ron => &["ron"],
+            #[cfg(feature = "json")]
+            ManifestFormat::Json => &["json"],
+            #[cfg(feature = "yaml")]
+            ManifestFormat::Yaml => &["yaml", "yml"],
+            #[cfg(feature = "toml")]
+            ManifestFormat::Toml => &["toml"],
+            #[cfg(feature = "xml")]
+            ManifestFormat::Xml => &["xml"],
+            #[cfg(feature = "csv")]
+            ManifestFormat::Csv => &["csv"],
+            #[cfg(feature = "msgpack")]
+            ManifestFormat::MsgPack => &["msgpack"],
+            ManifestFormat::Custom => &[],
+        }
+    }
+}
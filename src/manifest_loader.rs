@@ -1,97 +1,861 @@
-use crate::prelude::*;
-use bevy::{prelude::*, utils::HashMap};
-#[cfg(feature = "progress_tracking")]
-use iyes_progress::Progress;
+use crate::{
+    inheritance::{entry_names, inheriting_from, resolve_entries, EntryError, ResolvedEntries},
+    load_report::ErrorLocations,
+    prelude::*,
+    raw_value::RawValue,
+};
+use bevy::{
+    asset::{
+        io::{AssetReaderError, AssetSourceId},
+        AssetLoadFailedEvent, AssetPath, LoadedFolder, UntypedAssetId,
+    },
+    prelude::*,
+    tasks::{futures_lite::StreamExt, IoTaskPool},
+    utils::{HashMap, HashSet},
+};
 use std::{
     any::{type_name, TypeId},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
+/// A folder of manifests registered with [`RegisterPrototype::add_prototype_folder`]
+#[derive(Debug, Clone)]
+struct ManifestFolder {
+    path: PathBuf,
+    recursive: bool,
+}
+
+/// A folder of manifests that is being tracked by the loader
+#[derive(Debug)]
+struct LoadedManifestFolder {
+    folder: ManifestFolder,
+    /// Lists the folder's manifest files while it's being scanned
+    listing: Option<PooledTask<Result<Vec<PathBuf>, AssetReaderError>>>,
+    /// Loaded with [`AssetServer::load_folder`] while the asset server is watching for changes,
+    /// which reloads it when files are added to or removed from the folder. It's only used to find out
+    /// when to scan the folder again, its files are loaded with whichever loader was registered last
+    watch: Option<Handle<LoadedFolder>>,
+    /// The folder changed while it was being scanned, so it's scanned again once the scan finishes
+    rescan: bool,
+    scanned: bool,
+    failed: bool,
+    files: Vec<PathBuf>,
+}
+
+impl LoadedManifestFolder {
+    fn new<M: Manifest>(folder: ManifestFolder, asset_server: &AssetServer) -> Self {
+        let watch = (cfg!(feature = "hot_reload") && asset_server.watching_for_changes())
+            .then(|| asset_server.load_folder(folder.path.clone()));
+        let mut loaded = Self {
+            folder,
+            listing: None,
+            watch,
+            rescan: false,
+            scanned: false,
+            failed: false,
+            files: Vec::new(),
+        };
+        loaded.scan::<M>(asset_server);
+        loaded
+    }
+
+    /// Starts listing the folder's manifest files, or lists them again after the current listing
+    fn scan<M: Manifest>(&mut self, asset_server: &AssetServer) {
+        if self.listing.is_some() {
+            self.rescan = true;
+            return;
+        }
+
+        let task = list_files(
+            asset_server.clone(),
+            self.folder.clone(),
+            M::FORMAT.extensions(),
+        );
+        self.listing = Some(PooledTask::spawn(IoTaskPool::get(), task));
+    }
+
+    /// Returns the result of listing the folder, if it has finished
+    fn poll<M: Manifest>(
+        &mut self,
+        asset_server: &AssetServer,
+    ) -> Option<Result<Vec<PathBuf>, AssetReaderError>> {
+        let result = self.listing.as_mut()?.poll()?;
+        self.listing = None;
+        if std::mem::take(&mut self.rescan) {
+            self.scan::<M>(asset_server);
+        }
+        Some(result)
+    }
+}
+
+/// Lists the files in the folder with one of the `extensions`, and in its subfolders if it's recursive.
+/// Each file is loaded as the folder's manifest type, so files of other manifest types in the folder
+/// don't affect it, and a file that fails to load only fails itself
+async fn list_files(
+    asset_server: AssetServer,
+    folder: ManifestFolder,
+    extensions: &'static [&'static str],
+) -> Result<Vec<PathBuf>, AssetReaderError> {
+    let Ok(source) = asset_server.get_source(AssetSourceId::Default) else {
+        return Err(AssetReaderError::NotFound(folder.path));
+    };
+    let reader = source.reader();
+
+    let mut files = Vec::new();
+    let mut folders = vec![folder.path];
+    while let Some(path) = folders.pop() {
+        let mut entries = reader.read_directory(&path).await?;
+        while let Some(entry) = entries.next().await {
+            if reader.is_directory(&entry).await? {
+                if folder.recursive {
+                    folders.push(entry);
+                }
+            } else if entry
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| extensions.contains(&ext))
+            {
+                files.push(entry);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// The result of updating registered folders, see [`ManifestLoader::update_folders`]
+pub(crate) struct FolderChanges<P: Prototype> {
+    /// The prototypes of files that were removed from folders
+    pub changes: PrototypesChanged<P>,
+    /// The folders that loaded
+    pub loaded: Vec<Option<AssetPath<'static>>>,
+    pub failures: Vec<ManifestLoadFailed>,
+}
+
+/// The result of processing a manifest file
+pub struct ProcessedManifest<P: Prototype> {
+    pub conflicts: Vec<PrototypeConflict<P>>,
+    pub errors: Vec<ManifestEntryError>,
+    pub changes: PrototypesChanged<P>,
+    /// Files that were rejected, whose previous prototypes were kept
+    pub failures: Vec<ManifestLoadFailed>,
+    /// Every file that was processed, as files using inheritance are processed together
+    pub paths: Vec<Option<AssetPath<'static>>>,
+}
+
+/// The load state of every file registered for a manifest type.
+/// Query it with [`ManifestLoader::state`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ManifestState {
+    /// The manifest has been registered, but loading hasn't started
+    Registered,
+    /// The manifest's files are being loaded and processed for the first time
+    Loading,
+    /// Every file has been processed into the [`PrototypeLibrary`]
+    Processed,
+    /// Every file has settled, and at least one failed to load.
+    /// Prototypes from the other files are in the [`PrototypeLibrary`]
+    Failed,
+    /// The manifest had settled, and files registered or found in a folder since are being loaded.
+    /// Prototypes from earlier files are still in the [`PrototypeLibrary`].
+    /// Modified files are processed as soon as the asset server has reloaded them, without entering this state
+    Reloading,
+}
+
+impl ManifestState {
+    /// Returns true if prototypes from the manifest are available,
+    /// which is when every file has been processed, or while more are being loaded after that
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Self::Processed | Self::Reloading)
+    }
+}
+
+/// Tracks the manifest files registered with [`RegisterPrototype`], and their load state
 #[derive(Default, Resource, Debug)]
 pub struct ManifestLoader {
-    to_load: HashMap<TypeId, PathBuf>,
-    loaded: HashMap<TypeId, UntypedHandle>,
-    processed: HashMap<TypeId, UntypedHandle>,
-    #[cfg(feature = "progress_tracking")]
-    progress: Progress,
+    to_load: HashMap<TypeId, Vec<PathBuf>>,
+    folders_to_load: HashMap<TypeId, Vec<ManifestFolder>>,
+    loaded: HashMap<TypeId, Vec<UntypedHandle>>,
+    folders: HashMap<TypeId, Vec<LoadedManifestFolder>>,
+    /// Files found in folders that had already loaded when they were found
+    preloaded: HashMap<TypeId, Vec<UntypedAssetId>>,
+    processed: HashMap<TypeId, Vec<UntypedAssetId>>,
+    failed: HashMap<TypeId, Vec<UntypedAssetId>>,
+    /// Failed files that loaded, but whose entries were rejected when they were last resolved
+    rejected: HashSet<UntypedAssetId>,
+    /// The names of the entries in each file when it was last resolved with inheritance
+    names: HashMap<UntypedAssetId, HashSet<String>>,
+    /// Manifest types that have had every file processed or failed at least once
+    settled: HashSet<TypeId>,
+    /// The manifest types registered for each prototype type
+    outputs: HashMap<TypeId, Vec<TypeId>>,
+    error_locations: ErrorLocations,
+    validation_requested: bool,
 }
 
 impl ManifestLoader {
-    pub fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Default::default()
     }
 
-    #[cfg(feature = "progress_tracking")]
-    fn inc_progress_todo(&mut self) {
-        self.progress.total += 2;
+    /// The locations of parse errors, recorded by the loaders of formats that report them
+    pub(crate) fn error_locations(&self) -> ErrorLocations {
+        self.error_locations.clone()
+    }
+
+    /// The load progress of every registered manifest. See [`ManifestLoader::progress_for`]
+    pub fn progress(&self) -> ProtoProgress {
+        self.manifest_types()
+            .map(|type_id| self.progress_of(type_id))
+            .fold(ProtoProgress::default(), |total, progress| total + progress)
+    }
+
+    /// The load progress of the manifest, counted in files.
+    /// A file is done once it has been processed or has failed to load, and stays done when it's reloaded.
+    /// A folder counts as a single file until it has loaded
+    pub fn progress_for<M: Manifest>(&self) -> ProtoProgress {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        self.progress_of(type_id)
+    }
+
+    fn progress_of(&self, type_id: TypeId) -> ProtoProgress {
+        let pending = self.to_load.get(&type_id).map_or(0, Vec::len)
+            + self.folders_to_load.get(&type_id).map_or(0, Vec::len);
+        let unscanned = self
+            .folders
+            .get(&type_id)
+            .into_iter()
+            .flatten()
+            .filter(|f| !f.scanned)
+            .count();
+        let handles = self
+            .loaded
+            .get(&type_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let done = handles
+            .iter()
+            .filter(|h| self.is_file_settled(type_id, h.id()))
+            .count();
+
+        ProtoProgress {
+            done: done as u32,
+            total: (pending + unscanned + handles.len()) as u32,
+        }
+    }
+
+    /// Returns true if any file or folder has been registered for the manifest
+    pub fn is_registered<M: Manifest>(&self) -> bool {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        self.to_load.contains_key(&type_id)
+            || self.folders_to_load.contains_key(&type_id)
+            || self.loaded.contains_key(&type_id)
+            || self.folders.contains_key(&type_id)
     }
 
-    #[cfg(feature = "progress_tracking")]
-    fn inc_progress_done(&mut self) {
-        self.progress.done += 1;
+    /// Returns true if every registered manifest file has been loaded and processed,
+    /// or has failed to load
+    pub fn is_all_processed(&self) -> bool {
+        self.to_load.is_empty()
+            && self.folders_to_load.is_empty()
+            && self
+                .manifest_types()
+                .all(|type_id| self.is_settled(type_id))
     }
 
-    #[cfg(feature = "progress_tracking")]
-    pub fn progress(&self) -> &Progress {
-        &self.progress
+    /// Returns true if any manifest file or folder failed to load, and hasn't loaded successfully since
+    pub(crate) fn is_any_failed(&self) -> bool {
+        self.manifest_types()
+            .any(|type_id| self.has_failures(type_id))
     }
 
-    pub fn register<M: Manifest>(&mut self, path: impl Into<PathBuf>) {
+    /// Returns true if any file or folder of the manifest type failed to load, and hasn't loaded since
+    fn has_failures(&self, type_id: TypeId) -> bool {
+        self.failed.get(&type_id).is_some_and(|f| !f.is_empty())
+            || self
+                .folders
+                .get(&type_id)
+                .is_some_and(|folders| folders.iter().any(|f| f.failed))
+    }
+
+    /// Returns the load state of the manifest, or None if it hasn't been registered
+    pub fn state<M: Manifest>(&self) -> Option<ManifestState> {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        self.state_of(type_id)
+    }
+
+    /// Returns true if every manifest registered for the prototype is ready.
+    /// Returns false if no manifest has been registered for it. See [`ManifestState::is_ready`]
+    pub fn prototypes_ready<P: Prototype>(&self) -> bool {
+        let Some(manifests) = self.outputs.get(&std::any::TypeId::of::<P>()) else {
+            return false;
+        };
+        manifests
+            .iter()
+            .all(|type_id| self.state_of(*type_id).is_some_and(|s| s.is_ready()))
+    }
+
+    /// Returns true if every registered manifest is ready. See [`ManifestState::is_ready`]
+    pub fn all_prototypes_ready(&self) -> bool {
+        self.manifest_types()
+            .all(|type_id| self.state_of(type_id).is_some_and(|s| s.is_ready()))
+    }
+
+    fn manifest_types(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.outputs.values().flatten().copied()
+    }
+
+    fn state_of(&self, type_id: TypeId) -> Option<ManifestState> {
+        let is_pending =
+            self.to_load.contains_key(&type_id) || self.folders_to_load.contains_key(&type_id);
+        let is_started = self.loaded.contains_key(&type_id) || self.folders.contains_key(&type_id);
+        if !is_pending && !is_started {
+            return None;
+        }
+
+        let state = if self.is_settled(type_id) {
+            if self.has_failures(type_id) {
+                ManifestState::Failed
+            } else {
+                ManifestState::Processed
+            }
+        } else if self.settled.contains(&type_id) {
+            ManifestState::Reloading
+        } else if is_started {
+            ManifestState::Loading
+        } else {
+            ManifestState::Registered
+        };
+
+        Some(state)
+    }
+
+    /// Returns true if every file of the manifest type has been processed or has failed to load
+    fn is_settled(&self, type_id: TypeId) -> bool {
+        !self.to_load.contains_key(&type_id)
+            && !self.folders_to_load.contains_key(&type_id)
+            && self
+                .folders
+                .get(&type_id)
+                .into_iter()
+                .flatten()
+                .all(|f| f.scanned)
+            && self
+                .loaded
+                .get(&type_id)
+                .into_iter()
+                .flatten()
+                .all(|h| self.is_file_settled(type_id, h.id()))
+    }
+
+    /// Returns true if the file has been processed or has failed to load
+    fn is_file_settled(&self, type_id: TypeId, source: UntypedAssetId) -> bool {
+        self.processed
+            .get(&type_id)
+            .is_some_and(|p| p.contains(&source))
+            || self
+                .failed
+                .get(&type_id)
+                .is_some_and(|f| f.contains(&source))
+    }
+
+    /// Records that the manifest was settled, so loading more of its files counts as reloading
+    fn update_settled(&mut self, type_id: TypeId) {
+        if self.is_settled(type_id) {
+            self.settled.insert(type_id);
+        }
+    }
+
+    /// Returns true if prototype references should be validated, because a manifest
+    /// was processed since the last validation and every manifest has been processed
+    pub(crate) fn take_validation_request(&mut self) -> bool {
+        if !self.validation_requested || !self.is_all_processed() {
+            return false;
+        }
+
+        self.validation_requested = false;
+        true
+    }
+
+    /// Records that `M` produces prototypes of type `P`
+    pub(crate) fn register_output<M: Manifest<Output = P>, P: Prototype>(&mut self) {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        let manifests = self.outputs.entry(std::any::TypeId::of::<P>()).or_default();
+        if !manifests.contains(&type_id) {
+            manifests.push(type_id);
+        }
+    }
+
+    pub(crate) fn register<M: Manifest>(&mut self, path: impl Into<PathBuf>) {
         let path: PathBuf = path.into();
         let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
 
-        self.to_load.insert(type_id, path);
+        self.to_load.entry(type_id).or_default().push(path);
+    }
+
+    pub(crate) fn register_folder<M: Manifest>(
+        &mut self,
+        path: impl Into<PathBuf>,
+        recursive: bool,
+    ) {
+        let path: PathBuf = path.into();
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
 
-        #[cfg(feature = "progress_tracking")]
-        self.inc_progress_todo();
+        self.folders_to_load
+            .entry(type_id)
+            .or_default()
+            .push(ManifestFolder { path, recursive });
     }
 
-    pub fn load<M: Manifest>(&mut self, asset_server: &mut AssetServer) {
+    pub(crate) fn load<M: Manifest>(&mut self, asset_server: &mut AssetServer) {
         let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
-        let Some(path) = self.to_load.get(&type_id) else {
+        let paths = self.to_load.remove(&type_id);
+        let folders = self.folders_to_load.remove(&type_id);
+        if paths.is_none() && folders.is_none() {
             error!("Load called without path: {:?}", type_name::<M>());
             return;
+        }
+
+        for path in paths.into_iter().flatten() {
+            let handle = asset_server.load::<ManifestCollection<M>>(path).untyped();
+            self.loaded.entry(type_id).or_default().push(handle);
+        }
+
+        for folder in folders.into_iter().flatten() {
+            let loaded = LoadedManifestFolder::new::<M>(folder, asset_server);
+            self.folders.entry(type_id).or_default().push(loaded);
+        }
+    }
+
+    /// Returns true if loading has started for every registered file and folder
+    pub(crate) fn is_loaded<M: Manifest>(&self) -> bool {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        !self.to_load.contains_key(&type_id) && !self.folders_to_load.contains_key(&type_id)
+    }
+
+    /// Returns true if any of the manifest's folders is being scanned
+    pub(crate) fn is_scanning<M: Manifest>(&self) -> bool {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        self.folders
+            .get(&type_id)
+            .is_some_and(|folders| folders.iter().any(|f| f.listing.is_some()))
+    }
+
+    /// Tracks the files of registered folders that have been scanned, and removes the prototypes of files
+    /// that have disappeared from them. Returns the prototypes that were removed, and the folders that failed to load.
+    ///
+    /// If 'hot_reload' is enabled and the asset server is watching for changes,
+    /// folders are scanned again when files are added to or removed from them, which changes their `watch` folder
+    pub(crate) fn update_folders<M: Manifest<Output = P>, P: Prototype>(
+        &mut self,
+        asset_server: &AssetServer,
+        changed: &[AssetId<LoadedFolder>],
+        protos: &mut PrototypeLibrary<P>,
+    ) -> FolderChanges<P> {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        let mut result = FolderChanges {
+            changes: PrototypesChanged::default(),
+            loaded: Vec::new(),
+            failures: Vec::new(),
+        };
+        let Some(folders) = self.folders.get_mut(&type_id) else {
+            return result;
         };
 
-        let handle = asset_server
-            .load::<ManifestCollection<M>>(path.clone())
-            .untyped();
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        for loaded in folders.iter_mut() {
+            let is_changed = loaded
+                .watch
+                .as_ref()
+                .is_some_and(|watch| changed.contains(&watch.id()));
+            if is_changed {
+                loaded.scan::<M>(asset_server);
+            }
+            let Some(listing) = loaded.poll::<M>(asset_server) else {
+                continue;
+            };
 
-        self.loaded.insert(type_id, handle);
-        self.to_load.remove(&type_id);
+            loaded.scanned = true;
+            let path = AssetPath::from(loaded.folder.path.clone());
+            let files = match listing {
+                Ok(files) => files,
+                Err(error) => {
+                    loaded.failed = true;
+                    // The folder couldn't be read, so there's no location in a file
+                    result.failures.push(ManifestLoadFailed::new::<M>(
+                        path,
+                        format!("Could not read the folder: {error}"),
+                        None,
+                    ));
+                    continue;
+                }
+            };
 
-        #[cfg(feature = "progress_tracking")]
-        self.inc_progress_done();
+            loaded.failed = false;
+            result.loaded.push(Some(path));
+            for file in files.iter().filter(|f| !loaded.files.contains(f)) {
+                added.push(file.clone());
+            }
+            for file in loaded.files.iter().filter(|f| !files.contains(f)) {
+                removed.push(file.clone());
+            }
+            loaded.files = files;
+        }
+
+        for path in added {
+            let handle = asset_server.load::<ManifestCollection<M>>(path).untyped();
+            // The file may be registered for the manifest already, so its load event has been sent
+            if asset_server.is_loaded_with_dependencies(handle.id()) {
+                self.preloaded.entry(type_id).or_default().push(handle.id());
+            }
+            self.loaded.entry(type_id).or_default().push(handle);
+        }
+
+        for path in removed {
+            result
+                .changes
+                .extend(self.remove_file::<P>(type_id, &path, protos));
+        }
+
+        self.update_settled(type_id);
+        result
+    }
+
+    /// Stops tracking a file that was removed from a folder, and removes its prototypes
+    fn remove_file<P: Prototype>(
+        &mut self,
+        type_id: TypeId,
+        path: &Path,
+        protos: &mut PrototypeLibrary<P>,
+    ) -> PrototypesChanged<P> {
+        let handles = self.loaded.entry(type_id).or_default();
+        let Some(index) = handles
+            .iter()
+            .position(|h| h.path().is_some_and(|p| p.path() == path))
+        else {
+            return PrototypesChanged::default();
+        };
+
+        let source = handles.remove(index).id();
+        if let Some(processed) = self.processed.get_mut(&type_id) {
+            processed.retain(|id| *id != source);
+        }
+        self.clear_failed(type_id, source);
+        self.rejected.remove(&source);
+        self.names.remove(&source);
+        self.validation_requested = true;
+        protos.remove_source(source)
+    }
+
+    /// Takes the files found in folders that had already loaded, which still need to be processed
+    pub(crate) fn take_preloaded<M: Manifest>(&mut self) -> Vec<AssetId<ManifestCollection<M>>> {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        self.preloaded
+            .remove(&type_id)
+            .into_iter()
+            .flatten()
+            .map(|id| id.typed())
+            .collect()
     }
 
-    pub fn is_loaded<M: Manifest>(&self) -> bool {
+    /// Records that a manifest file failed to load. Any prototypes from a previous
+    /// successful load of the file are kept. Returns None if the file isn't tracked by the loader.
+    pub(crate) fn fail<M: Manifest>(
+        &mut self,
+        event: &AssetLoadFailedEvent<ManifestCollection<M>>,
+    ) -> Option<ManifestLoadFailed> {
         let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
-        self.loaded.contains_key(&type_id)
+        let source = event.id.untyped();
+        let is_tracked = self
+            .loaded
+            .get(&type_id)
+            .is_some_and(|handles| handles.iter().any(|h| h.id() == source));
+        if !is_tracked {
+            return None;
+        }
+
+        let failed = self.failed.entry(type_id).or_default();
+        if !failed.contains(&source) {
+            failed.push(source);
+        }
+
+        self.update_settled(type_id);
+
+        let failure = ManifestLoadFailed::new::<M>(
+            event.path.clone(),
+            event.error.to_string(),
+            self.error_locations.take(&event.path),
+        );
+        error!(
+            "Failed to load manifest {} for {}: {}",
+            failure.path, failure.manifest, failure.message
+        );
+
+        Some(failure)
     }
 
-    pub fn process<M: Manifest<Output = P>, P: Prototype>(
+    /// Processes a loaded manifest file into the PrototypeLibrary.
+    /// Returns the conflicts and invalid entries found while inserting its prototypes,
+    /// or None if the file isn't tracked by the loader.
+    pub(crate) fn process<M: Manifest<Output = P>, P: Prototype>(
         &mut self,
+        id: AssetId<ManifestCollection<M>>,
         assets: &mut Assets<ManifestCollection<M>>,
         protos: &mut PrototypeLibrary<P>,
-    ) {
-        let id = std::any::TypeId::of::<ManifestCollection<M>>();
-        let untyped_handle = self.loaded.get(&id).unwrap();
-        let typed_handle = untyped_handle.clone_weak().typed::<ManifestCollection<M>>();
+        policy: ConflictPolicy,
+    ) -> Option<ProcessedManifest<P>> {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        let source = id.untyped();
+        let is_tracked = self
+            .loaded
+            .get(&type_id)
+            .is_some_and(|handles| handles.iter().any(|h| h.id() == source));
+        if !is_tracked {
+            return None;
+        }
 
-        let Some(manifest) = assets.get(&typed_handle) else {
+        let Some(manifest) = assets.get(id) else {
             error!("Failed to get manifest: {:?}", id);
-            return;
+            return None;
         };
 
-        protos.clear();
-        for m in manifest {
-            let item: P = m.reify();
-            protos.insert(item);
+        let entries = if M::INHERITANCE || M::LENIENT {
+            self.resolve_raw::<M>(source, assets)
+        } else {
+            let entries = manifest
+                .into_iter()
+                .enumerate()
+                .map(|(i, m)| (format!("#{i}"), Ok(m)))
+                .collect();
+            vec![(source, entries)]
+        };
+
+        let mut processed = ProcessedManifest {
+            conflicts: Vec::new(),
+            errors: Vec::new(),
+            changes: PrototypesChanged::default(),
+            failures: Vec::new(),
+            paths: Vec::new(),
+        };
+        for (source, entries) in entries {
+            let path = self.source_path(source);
+            let mut items = Vec::new();
+            let mut errors = Vec::new();
+            for (entry, result) in entries {
+                let reified = result
+                    .map_err(|e| e.to_string())
+                    .and_then(|m| m.try_reify().map_err(|e| e.to_string()));
+                match reified {
+                    Ok(item) => items.push(item),
+                    Err(message) => errors.push(ManifestEntryError {
+                        manifest: type_name::<M>(),
+                        path: path.clone(),
+                        entry,
+                        message,
+                    }),
+                }
+            }
+
+            for error in &errors {
+                error!(
+                    "Invalid entry {:?} in {:?} for {}: {}",
+                    error.entry, error.path, error.manifest, error.message
+                );
+            }
+            let rejection = if !M::LENIENT && !errors.is_empty() {
+                Some("Rejected every entry because of invalid entries")
+            } else {
+                let (found, changes) = protos.replace_source(source, items, policy);
+                let conflicted = !found.is_empty();
+                processed.changes.extend(changes);
+                processed.conflicts.extend(
+                    found
+                        .into_iter()
+                        .map(|conflict| self.report_conflict(conflict, source, policy)),
+                );
+                (policy == ConflictPolicy::Error && conflicted)
+                    .then_some("Rejected every entry because of conflicting ids")
+            };
+
+            if let Some(message) = rejection {
+                processed
+                    .failures
+                    .extend(self.reject::<M>(source, path.clone(), message));
+            } else if self.rejected.remove(&source) && source != id.untyped() {
+                self.clear_failed(type_id, source);
+            }
+            processed.errors.extend(errors);
+            processed.paths.push(path);
         }
 
-        self.processed.insert(id, untyped_handle.clone());
+        if !self.rejected.contains(&source) {
+            self.clear_failed(type_id, source);
+        }
+        let processed_sources = self.processed.entry(type_id).or_default();
+        if !processed_sources.contains(&source) {
+            processed_sources.push(source);
+        }
+        self.update_settled(type_id);
+        self.validation_requested = true;
+
+        Some(processed)
+    }
+
+    /// Records that the entries of a file were rejected. Its prototypes from the last time
+    /// it was accepted are kept, as they are when it fails to load
+    fn reject<M: Manifest>(
+        &mut self,
+        source: UntypedAssetId,
+        path: Option<AssetPath<'static>>,
+        message: &str,
+    ) -> Option<ManifestLoadFailed> {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        let failed = self.failed.entry(type_id).or_default();
+        if !failed.contains(&source) {
+            failed.push(source);
+        }
+        self.rejected.insert(source);
+
+        let failure = ManifestLoadFailed::new::<M>(path?, message.to_string(), None);
+        error!(
+            "Failed to load manifest {} for {}: {}",
+            failure.path, failure.manifest, failure.message
+        );
+        Some(failure)
+    }
+
+    fn clear_failed(&mut self, type_id: TypeId, source: UntypedAssetId) {
+        if let Some(failed) = self.failed.get_mut(&type_id) {
+            failed.retain(|id| *id != source);
+        }
+    }
+
+    /// Deserializes the raw entries of `source` one at a time.
+    ///
+    /// If the manifest supports inheritance, processed files with entries inheriting from `source`'s entries,
+    /// before or after it changed, are resolved again as well. Files are returned in the order they were registered
+    fn resolve_raw<M: Manifest>(
+        &mut self,
+        source: UntypedAssetId,
+        assets: &Assets<ManifestCollection<M>>,
+    ) -> Vec<(UntypedAssetId, ResolvedEntries<M>)> {
+        let type_id = std::any::TypeId::of::<ManifestCollection<M>>();
+        let handles = self
+            .loaded
+            .get(&type_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let mut files: Vec<(UntypedAssetId, &[RawValue])> = Vec::new();
+        for handle in handles
+            .iter()
+            .filter(|h| M::INHERITANCE || h.id() == source)
+        {
+            let Some(manifest) = assets.get(handle.id().typed::<ManifestCollection<M>>()) else {
+                continue;
+            };
+            if !files.iter().any(|(id, _)| *id == handle.id()) {
+                files.push((handle.id(), manifest.raw.as_slice()));
+            }
+        }
+
+        // Parents may be in a file that hasn't loaded yet
+        let all_loaded = !M::INHERITANCE
+            || handles
+                .iter()
+                .all(|h| files.iter().any(|(id, _)| *id == h.id()));
+
+        let mut resolve = vec![source];
+        if M::INHERITANCE {
+            let processed = self.processed.get(&type_id);
+            let mut changed = self.names.remove(&source).unwrap_or_default();
+            changed.extend(
+                files
+                    .iter()
+                    .filter(|(id, _)| *id == source)
+                    .flat_map(|(_, entries)| entry_names(entries))
+                    .map(str::to_string),
+            );
+
+            // Entries inheriting from a changed entry change too, so their children are resolved as well
+            loop {
+                let before = (resolve.len(), changed.len());
+                let dependents: Vec<_> = inheriting_from(&files, &changed)
+                    .filter(|(id, _)| processed.is_some_and(|p| p.contains(id)))
+                    .map(|(id, name)| (id, name.map(str::to_string)))
+                    .collect();
+                for (id, name) in dependents {
+                    if !resolve.contains(&id) {
+                        resolve.push(id);
+                    }
+                    changed.extend(name);
+                }
+                if (resolve.len(), changed.len()) == before {
+                    break;
+                }
+            }
+
+            for (id, entries) in files.iter().filter(|(id, _)| resolve.contains(id)) {
+                let names = entry_names(entries).map(str::to_string).collect();
+                self.names.insert(*id, names);
+            }
+        }
+
+        resolve_entries::<M>(&files, &resolve)
+            .into_iter()
+            .map(|(id, entries)| {
+                let entries = entries
+                    .into_iter()
+                    .filter(|(_, result)| {
+                        all_loaded || !matches!(result, Err(EntryError::MissingParent { .. }))
+                    })
+                    .collect();
+                (id, entries)
+            })
+            .collect()
+    }
+
+    fn report_conflict<P: Prototype>(
+        &self,
+        conflict: SourceConflict<P>,
+        source: UntypedAssetId,
+        policy: ConflictPolicy,
+    ) -> PrototypeConflict<P> {
+        let conflict = PrototypeConflict {
+            id: conflict.id,
+            kind: conflict.kind,
+            existing_name: conflict.existing_name,
+            existing_path: self.source_path(conflict.existing_source),
+            incoming_name: conflict.incoming_name,
+            incoming_path: self.source_path(source),
+            policy,
+        };
+
+        let message = format!(
+            "{:?} for {}: {:?} in {:?} conflicts with {:?} in {:?}, resolved with {:?}",
+            conflict.kind,
+            type_name::<P>(),
+            conflict.incoming_name,
+            conflict.incoming_path,
+            conflict.existing_name,
+            conflict.existing_path,
+            policy,
+        );
+        match policy {
+            ConflictPolicy::Error => error!("{message}"),
+            _ => warn!("{message}"),
+        }
+
+        conflict
+    }
 
-        #[cfg(feature = "progress_tracking")]
-        self.inc_progress_done();
+    fn source_path(&self, source: UntypedAssetId) -> Option<AssetPath<'static>> {
+        self.loaded
+            .values()
+            .flatten()
+            .find(|h| h.id() == source)
+            .and_then(|h| h.path())
+            .cloned()
     }
 }
@@ -7,12 +7,41 @@ use std::fmt::Debug;
 pub trait Manifest: Debug + Clone + Asset + for<'de> Deserialize<'de> {
     /// The format of the manifest (json, ron, etc)
     const FORMAT: ManifestFormat;
+    /// Whether entries can inherit from another entry of the same type.
+    ///
+    /// When enabled, an entry can name a `parent` entry (from any file of this manifest type),
+    /// and the parent's fields are deep merged under the entry's own fields before it is deserialized.
+    /// Chains of parents are supported. Entries with `abstract: true` are only used as parents,
+    /// and never become prototypes. Parents are found by their `name` field, which must be unique
+    /// across the files, or the entries inheriting from it are rejected.
+    ///
+    /// Entries are read in a format independent way before merging, so enums must be written
+    /// in their externally tagged form (`"Variant"` or `{"Variant": value}`). RON's `Variant`,
+    /// `Variant(value)` and `Variant(field: value)` syntax loses the variant name, so it's rejected
+    /// with an entry error.
+    const INHERITANCE: bool = false;
+    /// Whether an invalid entry is skipped, instead of rejecting every entry in its file.
+    /// A rejected file keeps the prototypes from the last time it was accepted,
+    /// and is reported with a [`ManifestLoadFailed`].
+    ///
+    /// Either way, every invalid entry is reported with a [`ManifestEntryError`] and in the [`ManifestLoadReport`].
+    /// When enabled, entries are deserialized one at a time, with the same limitation on
+    /// enums as [`Manifest::INHERITANCE`].
+    const LENIENT: bool = false;
     /// The prototype this manifest generates
     type Output;
     /// Converts the Manifest into Output
     fn reify(&self) -> Self::Output;
+    /// Converts the Manifest into Output, or fails if the manifest is invalid.
+    /// By default this calls [`Manifest::reify`]
+    fn try_reify(&self) -> Result<Self::Output, ReifyError> {
+        Ok(self.reify())
+    }
 }
 
+/// The error returned by [`Manifest::try_reify`]
+pub type ReifyError = Box<dyn std::error::Error + Send + Sync>;
+
 pub trait AccessManifestFormat {
     fn manifest_format() -> ManifestFormat;
 }
@@ -0,0 +1,65 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use std::any::type_name;
+
+/// What happens when a prototype of type `P` is looked up by [`Id`] or name when spawning,
+/// and isn't in the [`PrototypeLibrary`].
+///
+/// Insert this as a resource to change the policy, the default is [`MissingPrototypePolicy::Log`].
+#[derive(Resource, Debug, Clone, Default)]
+pub enum MissingPrototypePolicy<P: Prototype> {
+    /// Logs an error and builds nothing, spawned entities are left empty
+    #[default]
+    Log,
+    /// Panics when the command is applied
+    Panic,
+    /// Builds the placeholder prototype instead. Logs an error if the placeholder is missing too
+    Placeholder(Id<P>),
+}
+
+/// Gets the prototype `id` from the library, applying the [`MissingPrototypePolicy`] if it isn't there
+pub(crate) fn resolve_prototype<P: Prototype>(world: &World, id: Id<P>) -> Option<P> {
+    let library = world.get_resource::<PrototypeLibrary<P>>();
+    if let Some(proto) = library.and_then(|protos| protos.get(&id)) {
+        return Some(proto);
+    }
+
+    let policy = world
+        .get_resource::<MissingPrototypePolicy<P>>()
+        .cloned()
+        .unwrap_or_default();
+    match policy {
+        MissingPrototypePolicy::Log => {
+            error!(
+                "Prototype {} not found for {}",
+                id.describe(),
+                type_name::<P>()
+            );
+            None
+        }
+        MissingPrototypePolicy::Panic => {
+            panic!(
+                "Prototype {} not found for {}",
+                id.describe(),
+                type_name::<P>()
+            )
+        }
+        MissingPrototypePolicy::Placeholder(placeholder) => {
+            warn!(
+                "Prototype {} not found for {}, using placeholder {}",
+                id.describe(),
+                type_name::<P>(),
+                placeholder.describe()
+            );
+            let proto = library.and_then(|protos| protos.get(&placeholder));
+            if proto.is_none() {
+                error!(
+                    "Placeholder prototype {} not found for {}",
+                    placeholder.describe(),
+                    type_name::<P>()
+                );
+            }
+            proto
+        }
+    }
+}
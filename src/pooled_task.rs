@@ -0,0 +1,38 @@
+use bevy::tasks::{futures_lite::future, TaskPool};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
+
+/// A future running on a task pool, whose result is taken with [`PooledTask::poll`].
+/// Unlike [`Task`](bevy::tasks::Task) it works without bevy's `multi_threaded` feature,
+/// whose single threaded task pools don't return a task that can be polled.
+/// Dropping it cancels the future the next time the future is polled
+#[derive(Debug)]
+pub(crate) struct PooledTask<T>(Arc<Mutex<Option<T>>>);
+
+impl<T: Send + 'static> PooledTask<T> {
+    pub(crate) fn spawn(pool: &TaskPool, future: impl Future<Output = T> + Send + 'static) -> Self {
+        let result = Arc::new(Mutex::new(None));
+        let slot = Arc::downgrade(&result);
+        let mut future = Box::pin(future);
+        pool.spawn(future::poll_fn(move |cx| {
+            let Some(slot) = slot.upgrade() else {
+                return Poll::Ready(());
+            };
+            let value = std::task::ready!(future.as_mut().poll(cx));
+            if let Ok(mut slot) = slot.lock() {
+                *slot = Some(value);
+            }
+            Poll::Ready(())
+        }))
+        .detach();
+        Self(result)
+    }
+
+    /// Takes the result of the future, if it has finished
+    pub(crate) fn poll(&mut self) -> Option<T> {
+        self.0.lock().ok()?.take()
+    }
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+/// How many manifest files are done loading, out of the total.
+/// See [`ManifestLoader::progress_for`](crate::prelude::ManifestLoader::progress_for).
+/// Kept up to date by [`ProtoPlugin`](crate::prelude::ProtoPlugin), with or without the 'progress_tracking' feature
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProtoProgress {
+    /// The number of files done
+    pub done: u32,
+    /// The number of files known so far. This grows as folders load
+    pub total: u32,
+}
+
+impl ProtoProgress {
+    /// The fraction of files done, from 0 to 1. This is 1 if there's nothing to load
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        self.done as f32 / self.total as f32
+    }
+
+    /// Returns true if every known file is done
+    pub fn is_done(&self) -> bool {
+        self.done >= self.total
+    }
+}
+
+impl std::ops::Add for ProtoProgress {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            done: self.done + other.done,
+            total: self.total + other.total,
+        }
+    }
+}
+
+#[cfg(feature = "progress_tracking")]
+impl From<ProtoProgress> for iyes_progress::Progress {
+    fn from(progress: ProtoProgress) -> Self {
+        Self {
+            done: progress.done,
+            total: progress.total,
+        }
+    }
+}
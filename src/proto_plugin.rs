@@ -1,34 +1,98 @@
-use crate::{prelude::*, systems::track_progress};
-use bevy::prelude::*;
-use iyes_progress::{ProgressSystem, TrackedProgressSet};
+#[cfg(feature = "progress_tracking")]
+use crate::systems::track_progress;
+use crate::{
+    prelude::*,
+    systems::{finish_loading, update_progress},
+};
+use bevy::{prelude::*, state::state::FreelyMutableState};
+#[cfg(feature = "progress_tracking")]
+use iyes_progress::{ProgressCounter, ProgressSystem, TrackedProgressSet};
+
+/// Adds the systems for a loading state to the app
+type AddLoadingState = Box<dyn Fn(&mut App) + Send + Sync>;
 
 /// Plugin for all of rantz_proto. Add this to your app.
-pub struct ProtoPlugin;
+///
+/// Use [`ProtoPlugin::with_loading_state`] to change state once every prototype has loaded
+#[derive(Default)]
+pub struct ProtoPlugin {
+    loading_state: Option<AddLoadingState>,
+}
 
 impl Plugin for ProtoPlugin {
     fn build(&self, app: &mut App) {
         Self::init_resources(app);
         Self::add_systems(app);
+
+        if let Some(add_loading_state) = &self.loading_state {
+            add_loading_state(app);
+        }
     }
 }
 
 impl ProtoPlugin {
+    /// Creates the plugin without a loading state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only starts loading manifests in the `loading` state, and moves to the `next` state
+    /// once every registered manifest has been processed, or to the `failed` state if any failed to load.
+    ///
+    /// If the state hasn't been added to the app, it's inserted in the `loading` state.
+    /// Manifests that are already loading keep being tracked outside of the `loading` state,
+    /// so hot reloading and reference validation still work after it
+    pub fn with_loading_state<S: FreelyMutableState>(
+        mut self,
+        loading: S,
+        next: S,
+        failed: S,
+    ) -> Self {
+        self.loading_state = Some(Box::new(move |app: &mut App| {
+            if !app.world().contains_resource::<State<S>>() {
+                app.insert_state(loading.clone());
+            }
+
+            app.configure_sets(
+                Update,
+                ProtoSchedule::StartLoading.run_if(in_state(loading.clone())),
+            );
+            app.add_systems(
+                Update,
+                finish_loading(next.clone(), failed.clone())
+                    .after(ProtoSchedule::Loading)
+                    .run_if(in_state(loading.clone())),
+            );
+        }));
+        self
+    }
+
     fn init_resources(app: &mut App) {
         app.init_resource::<ManifestLoader>();
+        app.init_resource::<ConflictPolicy>();
+        app.init_resource::<ManifestLoadReport>();
+        app.init_resource::<ProtoProgress>();
     }
 
     fn add_systems(app: &mut App) {
+        app.add_event::<DanglingReference>();
+        app.add_event::<ManifestEntryError>();
+        app.add_event::<ManifestLoadFailed>();
         app.add_systems(
             PostUpdate,
             handle_async_spawn.in_set(ProtoSchedule::Spawning),
         );
+        app.add_systems(Update, validate_references.in_set(ProtoSchedule::Loading));
+        app.add_systems(Update, update_progress.in_set(ProtoSchedule::Loading));
+        // iyes_progress only has a counter while one of its tracked states is active
         #[cfg(feature = "progress_tracking")]
         app.add_systems(
             Update,
             track_progress
                 .track_progress()
                 .in_set(TrackedProgressSet)
-                .in_set(ProtoSchedule::Loading),
+                .in_set(ProtoSchedule::Loading)
+                .run_if(resource_exists::<ProgressCounter>),
         );
     }
 }
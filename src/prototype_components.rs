@@ -0,0 +1,151 @@
+use crate::prelude::*;
+use bevy::{ecs::component::ComponentId, prelude::*};
+
+/// The prototype an entity was spawned from, or last had applied with
+/// [`PrototypeEntityExt::replace_prototype`]
+#[derive(Debug, Clone, Component, PartialEq, Eq)]
+pub struct FromPrototype<P: Prototype>(pub Id<P>);
+
+/// The components that an entity's prototype added or changed in its builds.
+/// Components added later, such as by gameplay systems, aren't included
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct PrototypeComponents {
+    /// Components the entity didn't have before the prototype was built
+    added: Vec<ComponentId>,
+    /// Components the entity already had, which the prototype overwrote
+    changed: Vec<ComponentId>,
+    /// The entity's [`FromPrototype`] component, which depends on the prototype type
+    origin: ComponentId,
+    name: String,
+    type_name: &'static str,
+}
+
+impl PrototypeComponents {
+    /// Returns true if the prototype added or changed the component
+    pub fn contains(&self, id: ComponentId) -> bool {
+        self.added.contains(&id) || self.changed.contains(&id)
+    }
+
+    /// Returns every component the prototype added or changed
+    pub fn iter(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.added.iter().chain(&self.changed).copied()
+    }
+
+    /// Returns the components the prototype added to the entity.
+    /// These are removed when the prototype is replaced
+    pub fn added(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.added.iter().copied()
+    }
+
+    /// Returns the components the entity already had, which the prototype overwrote.
+    /// These are kept when the prototype is replaced
+    pub fn changed(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.changed.iter().copied()
+    }
+
+    /// The name of the prototype
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The type name of the prototype
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+/// Sent when an entity's prototype is replaced with [`PrototypeEntityExt::replace_prototype`]
+#[derive(Event, Debug, Clone)]
+pub struct PrototypeReplaced<P: Prototype> {
+    /// The entity whose prototype was replaced
+    pub entity: Entity,
+    /// The new prototype
+    pub id: Id<P>,
+    /// The name of the previous prototype, or None if the entity didn't have one
+    pub previous_name: Option<String>,
+    /// The type name of the previous prototype, which may be a different type from `P`
+    pub previous_type: Option<&'static str>,
+}
+
+/// Builds a prototype on an entity that doesn't have one yet
+pub(crate) fn build_prototype<P: Prototype>(target: &mut EntityWorldMut, proto: &P) {
+    target.insert(FromPrototype(proto.id()));
+    build_tracked(target, proto, false, |e| proto.build(e));
+}
+
+/// Runs `build` on the entity, and records the components it added or changed in [`PrototypeComponents`].
+///
+/// Components are found by diffing the archetype before and after, and by their change ticks
+/// for components that were already on the entity. Components a previous build added stay
+/// tracked as added. If `remove_stale` is true, components a previous build added that this
+/// build didn't add or change are removed
+pub(crate) fn build_tracked<P: Prototype>(
+    target: &mut EntityWorldMut,
+    proto: &P,
+    remove_stale: bool,
+    build: impl FnOnce(&mut EntityWorldMut),
+) {
+    let before: Vec<ComponentId> = target.archetype().components().collect();
+    let previous = target.take::<PrototypeComponents>();
+    let last_run = target.world().increment_change_tick();
+    let this_run = target.world().read_change_tick();
+
+    build(target);
+
+    let origin = target.world_scope(|world| world.init_component::<FromPrototype<P>>());
+    let built: Vec<ComponentId> = target
+        .archetype()
+        .components()
+        .filter(|id| *id != origin)
+        .filter(|id| {
+            !before.contains(id)
+                || target
+                    .get_change_ticks_by_id(*id)
+                    .is_some_and(|ticks| ticks.is_changed(last_run, this_run))
+        })
+        .collect();
+
+    let mut added: Vec<ComponentId> = Vec::new();
+    if let Some(previous) = &previous {
+        for id in previous.added() {
+            if built.contains(&id) {
+                added.push(id);
+            } else if remove_stale {
+                target.remove_by_id(id);
+            } else if target.contains_id(id) {
+                added.push(id);
+            }
+        }
+    }
+    let (new, changed): (Vec<ComponentId>, Vec<ComponentId>) = built
+        .into_iter()
+        .filter(|id| !added.contains(id))
+        .partition(|id| !before.contains(id));
+    added.extend(new);
+
+    target.insert(PrototypeComponents {
+        added,
+        changed,
+        origin,
+        name: proto.name(),
+        type_name: std::any::type_name::<P>(),
+    });
+}
+
+/// Removes the components the entity's current prototype added, and its [`FromPrototype`],
+/// then builds `proto` on it. Components the previous prototype only changed are kept.
+/// Returns the components of the previous prototype
+pub(crate) fn replace_prototype<P: Prototype>(
+    target: &mut EntityWorldMut,
+    proto: &P,
+) -> Option<PrototypeComponents> {
+    let previous = target.take::<PrototypeComponents>();
+    if let Some(previous) = &previous {
+        for id in previous.added().chain([previous.origin]) {
+            target.remove_by_id(id);
+        }
+    }
+
+    instantiate(target, proto);
+    previous
+}
@@ -1,5 +1,10 @@
 use crate::prelude::*;
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{
+    asset::UntypedAssetId,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use std::sync::Arc;
 
 /// A generic library of prototypes. Accessed as a resource when you want
 /// to load prototypes of type P.
@@ -8,7 +13,27 @@ pub struct PrototypeLibrary<P>
 where
     P: Prototype,
 {
-    prototypes: HashMap<Id<P>, P>,
+    /// Every manifest entry that defines each id. The last one is the prototype in use,
+    /// and the others are used again if it's removed
+    definitions: HashMap<Id<P>, Vec<Definition<P>>>,
+    templates: Option<HashMap<Id<P>, Arc<ComponentTemplate>>>,
+}
+
+/// A manifest entry that defines a prototype
+#[derive(Debug)]
+struct Definition<P> {
+    source: UntypedAssetId,
+    name: String,
+    prototype: P,
+}
+
+/// A manifest entry that maps to the same id as an entry already in the library
+pub(crate) struct SourceConflict<P: Prototype> {
+    pub id: Id<P>,
+    pub kind: ConflictKind,
+    pub existing_name: String,
+    pub existing_source: UntypedAssetId,
+    pub incoming_name: String,
 }
 
 impl<P> PrototypeLibrary<P>
@@ -18,19 +43,15 @@ where
     /// Create a new PrototypeLibrary
     pub fn new() -> Self {
         Self {
-            prototypes: HashMap::new(),
+            definitions: HashMap::new(),
+            templates: None,
         }
     }
 
     /// Gets a prototype from the library by id, or None if the prototype doesn't exist
     #[must_use]
     pub fn get(&self, id: &Id<P>) -> Option<P> {
-        if let Some(p) = self.prototypes.get(id) {
-            let p: P = p.to_owned();
-            Some(p)
-        } else {
-            None
-        }
+        self.active(id).map(|d| d.prototype.to_owned())
     }
 
     /// Gets a prototype from the library by name, or None if the prototype doesn't exist
@@ -51,11 +72,21 @@ where
         None
     }
 
-    /// Gets mutable access to a prototype from the library by id,
+    /// Gets the name of the prototype with the given id,
     /// or None if the prototype doesn't exist
     #[must_use]
+    pub fn name(&self, id: &Id<P>) -> Option<&str> {
+        self.active(id).map(|d| d.name.as_str())
+    }
+
+    /// Gets mutable access to a prototype from the library by id,
+    /// or None if the prototype doesn't exist. If it has a template, it's rebuilt after the change
+    #[must_use]
     pub fn get_mut(&mut self, id: &Id<P>) -> Option<&mut P> {
-        self.prototypes.get_mut(id)
+        self.definitions
+            .get_mut(id)
+            .and_then(|definitions| definitions.last_mut())
+            .map(|d| &mut d.prototype)
     }
 
     /// Gets mutable access to a prototype from the library by name,
@@ -65,24 +96,201 @@ where
         self.get_mut(&Id::from_name(name))
     }
 
-    pub(crate) fn insert(&mut self, item: P) -> Id<P> {
-        let id = Id::from_name(&item.name());
-        self.prototypes.insert(id, item);
-        id
+    /// Returns true if the prototype has a template, which is cloned when it's spawned.
+    /// See [`RegisterPrototype::use_prototype_templates`]
+    pub fn has_template(&self, id: &Id<P>) -> bool {
+        self.templates
+            .as_ref()
+            .is_some_and(|templates| templates.contains_key(id))
+    }
+
+    /// Returns false if templates were already enabled
+    pub(crate) fn enable_templates(&mut self) -> bool {
+        let enabled = self.templates.is_none();
+        self.templates.get_or_insert_with(HashMap::new);
+        enabled
+    }
+
+    pub(crate) fn template(&self, id: &Id<P>) -> Option<Arc<ComponentTemplate>> {
+        self.templates.as_ref()?.get(id).cloned()
+    }
+
+    pub(crate) fn set_template(&mut self, id: Id<P>, template: Option<ComponentTemplate>) {
+        let Some(templates) = &mut self.templates else {
+            return;
+        };
+        match template {
+            Some(template) => templates.insert(id, Arc::new(template)),
+            None => templates.remove(&id),
+        };
+    }
+
+    /// The definition of the prototype in use
+    fn active(&self, id: &Id<P>) -> Option<&Definition<P>> {
+        self.definitions.get(id)?.last()
+    }
+
+    /// Replaces every prototype that was loaded from `source` with `items`,
+    /// leaving prototypes from other manifest files untouched.
+    ///
+    /// Entries that map to an id that is already in use are resolved with `policy`,
+    /// and returned as conflicts. With [`ConflictPolicy::Error`] the prototypes from `source`
+    /// are left as they were if there are any conflicts.
+    pub(crate) fn replace_source(
+        &mut self,
+        source: UntypedAssetId,
+        items: impl IntoIterator<Item = P>,
+        policy: ConflictPolicy,
+    ) -> (Vec<SourceConflict<P>>, PrototypesChanged<P>) {
+        let items: Vec<(String, P)> = items.into_iter().map(|p| (p.name(), p)).collect();
+        let affected = self
+            .ids_from(source)
+            .chain(items.iter().map(|(name, _)| Id::from_name(name)))
+            .collect();
+        let before = self.content_hashes(affected);
+
+        let conflicts = self.insert_source(source, items, policy);
+        let changes = self.changes_since(before);
+        (conflicts, changes)
+    }
+
+    fn insert_source(
+        &mut self,
+        source: UntypedAssetId,
+        items: Vec<(String, P)>,
+        policy: ConflictPolicy,
+    ) -> Vec<SourceConflict<P>> {
+        let mut conflicts = Vec::new();
+        let mut incoming: HashMap<Id<P>, usize> = HashMap::new();
+        for (index, (name, _)) in items.iter().enumerate() {
+            let id = Id::from_name(name);
+            let existing = incoming
+                .get(&id)
+                .map(|existing| (items[*existing].0.clone(), source))
+                .or_else(|| {
+                    let existing = self
+                        .definitions
+                        .get(&id)?
+                        .iter()
+                        .rev()
+                        .find(|d| d.source != source)?;
+                    Some((existing.name.clone(), existing.source))
+                });
+
+            if let Some((existing_name, existing_source)) = existing {
+                let kind = if existing_name == *name {
+                    ConflictKind::DuplicateName
+                } else {
+                    ConflictKind::HashCollision
+                };
+                conflicts.push(SourceConflict {
+                    id,
+                    kind,
+                    existing_name,
+                    existing_source,
+                    incoming_name: name.clone(),
+                });
+            }
+
+            incoming.insert(id, index);
+        }
+
+        if policy == ConflictPolicy::Error && !conflicts.is_empty() {
+            return conflicts;
+        }
+
+        // Reloaded definitions keep their place, so reloading a file doesn't change which definition is used
+        let positions: HashMap<Id<P>, usize> = self
+            .definitions
+            .iter()
+            .filter_map(|(id, definitions)| {
+                Some((*id, definitions.iter().position(|d| d.source == source)?))
+            })
+            .collect();
+        self.remove_source_items(source);
+
+        for (name, prototype) in items {
+            let id = Id::from_name(&name);
+            id.record_name(&name);
+            let definition = Definition {
+                source,
+                name,
+                prototype,
+            };
+
+            // The last definition is used, so with FirstWins later ones wait behind it
+            let definitions = self.definitions.entry(id).or_default();
+            let index = match positions.get(&id) {
+                Some(index) => (*index).min(definitions.len()),
+                None if policy == ConflictPolicy::FirstWins => 0,
+                None => definitions.len(),
+            };
+            definitions.insert(index, definition);
+        }
+
+        conflicts
+    }
+
+    /// Removes every prototype that was loaded from `source`.
+    /// Prototypes that other sources also define fall back to their definition
+    pub(crate) fn remove_source(&mut self, source: UntypedAssetId) -> PrototypesChanged<P> {
+        let before = self.content_hashes(self.ids_from(source).collect());
+        self.remove_source_items(source);
+        self.changes_since(before)
+    }
+
+    fn remove_source_items(&mut self, source: UntypedAssetId) {
+        self.definitions.retain(|_, definitions| {
+            definitions.retain(|d| d.source != source);
+            !definitions.is_empty()
+        });
+    }
+
+    /// Returns the ids of every prototype that was loaded from `source`
+    fn ids_from(&self, source: UntypedAssetId) -> impl Iterator<Item = Id<P>> + '_ {
+        self.definitions
+            .iter()
+            .filter(move |(_, definitions)| definitions.iter().any(|d| d.source == source))
+            .map(|(id, _)| *id)
+    }
+
+    /// Returns the content hash of each of the ids, or None if it isn't in the library
+    fn content_hashes(&self, ids: HashSet<Id<P>>) -> HashMap<Id<P>, Option<u64>> {
+        ids.into_iter()
+            .map(|id| (id, self.active(&id).map(|d| d.prototype.content_hash())))
+            .collect()
+    }
+
+    /// Compares the library with content hashes from [`PrototypeLibrary::content_hashes`]
+    fn changes_since(&self, before: HashMap<Id<P>, Option<u64>>) -> PrototypesChanged<P> {
+        let mut changes = PrototypesChanged::default();
+        for (id, old) in before {
+            let new = self.active(&id).map(|d| d.prototype.content_hash());
+            match (old, new) {
+                (None, Some(_)) => changes.added.push(id),
+                (Some(_), None) => changes.removed.push(id),
+                (Some(old), Some(new)) if old != new => changes.changed.push(id),
+                _ => (),
+            }
+        }
+        changes
     }
 
-    pub(crate) fn clear(&mut self) {
-        self.prototypes.clear();
+    /// Returns an iterator over every prototype in the library and its id
+    pub fn iter(&self) -> impl Iterator<Item = (&Id<P>, &P)> {
+        self.definitions
+            .iter()
+            .filter_map(|(id, definitions)| Some((id, &definitions.last()?.prototype)))
     }
 
     /// Returns true if the library is empty
     pub fn is_empty(&self) -> bool {
-        self.prototypes.is_empty()
+        self.definitions.is_empty()
     }
 
     /// Returns the number of prototypes in the library
     pub fn len(&self) -> usize {
-        self.prototypes.len()
+        self.definitions.len()
     }
 }
 
@@ -92,7 +300,8 @@ where
 {
     fn default() -> Self {
         Self {
-            prototypes: Default::default(),
+            definitions: Default::default(),
+            templates: None,
         }
     }
 }
@@ -1,7 +1,7 @@
 use bevy::prelude::EntityWorldMut;
-use std::fmt::Debug;
+use std::{fmt::Debug, future::Future};
 
-use crate::id::Id;
+use crate::{id::Id, references::PrototypeReference};
 
 /// A trait for building prototypes
 pub trait Prototype: Clone + Send + Sync + Debug + 'static {
@@ -17,7 +17,33 @@ pub trait Prototype: Clone + Send + Sync + Debug + 'static {
     /// Builds the prototype on a target entity
     fn build(&self, target: &mut EntityWorldMut);
 
-    /// Rebuilds the prototype on a target entity if 'hot_reload' is enabled
-    /// by default this does nothing
-    fn rebuild(&self, _: &mut EntityWorldMut) {}
+    /// Prepares the prototype off the main thread when it's spawned with
+    /// [`SpawnPrototypeAsyncExt`](crate::prelude::SpawnPrototypeAsyncExt), before [`Prototype::build`] runs.
+    /// By default this returns the prototype unchanged.
+    ///
+    /// Override this for heavy work, such as procedural generation, and store the result in the returned prototype
+    fn prepare(self) -> impl Future<Output = Self> + Send {
+        async { self }
+    }
+
+    /// The other prototypes this prototype references, which are checked to
+    /// exist once every manifest has been loaded. By default this is empty
+    fn references(&self) -> Vec<PrototypeReference> {
+        Vec::new()
+    }
+
+    /// A hash of the prototype's content, used to find which prototypes changed
+    /// when a manifest is reloaded. It must include everything `build` uses,
+    /// such as by deriving [`Hash`](std::hash::Hash) and hashing the prototype with a
+    /// [`DefaultHasher`](std::collections::hash_map::DefaultHasher)
+    fn content_hash(&self) -> u64;
+
+    /// Rebuilds the prototype on an entity it was spawned on, when 'hot_reload' is enabled
+    /// and the prototype changes. By default this calls [`Prototype::build`].
+    ///
+    /// Override this to keep runtime state, such as current health, that `build` would overwrite.
+    /// See [`ReloadPolicy`](crate::prelude::ReloadPolicy)
+    fn rebuild(&self, target: &mut EntityWorldMut) {
+        self.build(target);
+    }
 }
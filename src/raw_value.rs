@@ -0,0 +1,568 @@
+use serde::{
+    de::{
+        value::{Error, MapDeserializer, SeqDeserializer},
+        EnumAccess, Error as _, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+/// A format independent, self-describing manifest entry, used to merge
+/// entries with their parents before they are deserialized into a [`Manifest`](crate::prelude::Manifest)
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawValue {
+    Unit,
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<RawValue>),
+    Newtype(Box<RawValue>),
+    Seq(Vec<RawValue>),
+    Map(Vec<(RawValue, RawValue)>),
+}
+
+impl RawValue {
+    /// Gets the value of a field, if this is a map
+    pub fn get(&self, key: &str) -> Option<&RawValue> {
+        let RawValue::Map(entries) = self else {
+            return None;
+        };
+        entries
+            .iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Removes a field, if this is a map
+    pub fn remove(&mut self, key: &str) -> Option<RawValue> {
+        let RawValue::Map(entries) = self else {
+            return None;
+        };
+        let index = entries.iter().position(|(k, _)| k.as_str() == Some(key))?;
+        Some(entries.remove(index).1)
+    }
+
+    /// Returns the string, if this is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            RawValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the bool, if this is a bool
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            RawValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Deep merges `child` over `self`. Maps are merged field by field,
+    /// every other value is replaced by the child's value
+    pub fn merge(self, child: RawValue) -> RawValue {
+        match (self, child) {
+            (RawValue::Map(mut parent), RawValue::Map(child)) => {
+                for (key, value) in child {
+                    match parent.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, existing)) => {
+                            let old = std::mem::replace(existing, RawValue::Unit);
+                            *existing = old.merge(value);
+                        }
+                        None => parent.push((key, value)),
+                    }
+                }
+                RawValue::Map(parent)
+            }
+            (RawValue::Some(parent), RawValue::Some(child)) => {
+                RawValue::Some(Box::new(parent.merge(*child)))
+            }
+            (_, child) => child,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RawValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RawValueVisitor)
+    }
+}
+
+struct RawValueVisitor;
+
+impl<'de> Visitor<'de> for RawValueVisitor {
+    type Value = RawValue;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "any value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<RawValue, E> {
+        Ok(RawValue::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<RawValue, E> {
+        Ok(RawValue::I64(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<RawValue, E> {
+        Ok(RawValue::U64(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<RawValue, E> {
+        Ok(RawValue::F64(v))
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<RawValue, E> {
+        Ok(RawValue::Char(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<RawValue, E> {
+        Ok(RawValue::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<RawValue, E> {
+        Ok(RawValue::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<RawValue, E> {
+        Ok(RawValue::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<RawValue, E> {
+        Ok(RawValue::Bytes(v))
+    }
+
+    fn visit_none<E>(self) -> Result<RawValue, E> {
+        Ok(RawValue::None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<RawValue, D::Error> {
+        Ok(RawValue::Some(Box::new(RawValue::deserialize(
+            deserializer,
+        )?)))
+    }
+
+    fn visit_unit<E>(self) -> Result<RawValue, E> {
+        Ok(RawValue::Unit)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<RawValue, D::Error> {
+        Ok(RawValue::Newtype(Box::new(RawValue::deserialize(
+            deserializer,
+        )?)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<RawValue, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(RawValue::Seq(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RawValue, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(RawValue::Map(entries))
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for RawValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for RawValue {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            RawValue::Unit => visitor.visit_unit(),
+            RawValue::Bool(v) => visitor.visit_bool(v),
+            RawValue::I64(v) => visitor.visit_i64(v),
+            RawValue::U64(v) => visitor.visit_u64(v),
+            RawValue::F64(v) => visitor.visit_f64(v),
+            RawValue::Char(v) => visitor.visit_char(v),
+            RawValue::String(v) => visitor.visit_string(v),
+            RawValue::Bytes(v) => visitor.visit_byte_buf(v),
+            RawValue::None => visitor.visit_none(),
+            RawValue::Some(v) => visitor.visit_some(*v),
+            RawValue::Newtype(v) => visitor.visit_newtype_struct(*v),
+            RawValue::Seq(v) => visitor.visit_seq(SeqDeserializer::new(v.into_iter())),
+            RawValue::Map(v) => visitor.visit_map(MapDeserializer::new(v.into_iter())),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            RawValue::None | RawValue::Unit => visitor.visit_none(),
+            RawValue::Some(v) => visitor.visit_some(*v),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            RawValue::Newtype(v) => visitor.visit_newtype_struct(*v),
+            RawValue::Seq(v) if v.len() == 1 => visitor.visit_newtype_struct(NewtypeSeq(v)),
+            v => visitor.visit_newtype_struct(v),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            RawValue::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            RawValue::Map(mut entries)
+                if entries.len() == 1
+                    && entries[0].0.as_str().is_some_and(|v| variants.contains(&v)) =>
+            {
+                let (variant, value) = entries.remove(0);
+                visitor.visit_enum(RawEnum { variant, value })
+            }
+            // RON's `Variant`, `Variant(..)` and `Variant(field: ..)` lose the variant name when read
+            // without knowing the type, and look like a unit, a sequence or a struct
+            RawValue::Unit | RawValue::Seq(_) | RawValue::Map(_) => Err(Error::custom(
+                "expected an enum as \"Variant\" or {\"Variant\": value}, \
+                 RON's Variant(..) syntax can't be used with inheritance or lenient manifests",
+            )),
+            _ => Err(Error::custom(
+                "expected an enum variant name, or a map with a single variant",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// The value of a newtype struct that was read as a sequence of one value.
+/// RON's `Name(value)` is read like that without knowing the type, while other formats write
+/// the value on its own, which may be a sequence. The type being deserialized decides which it is
+struct NewtypeSeq(Vec<RawValue>);
+
+impl NewtypeSeq {
+    fn value(mut self) -> RawValue {
+        self.0.remove(0)
+    }
+
+    fn seq(self) -> RawValue {
+        if matches!(self.0[0], RawValue::Seq(_)) {
+            self.value()
+        } else {
+            RawValue::Seq(self.0)
+        }
+    }
+}
+
+macro_rules! forward_to_value {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.value().$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for NewtypeSeq {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        RawValue::Seq(self.0).deserialize_any(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.seq().deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.seq().deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.seq().deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.value().deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.value().deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.value().deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.value().deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_value! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char deserialize_str
+        deserialize_string deserialize_bytes deserialize_byte_buf deserialize_option
+        deserialize_unit deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+}
+
+struct RawEnum {
+    variant: RawValue,
+    value: RawValue,
+}
+
+impl<'de> EnumAccess<'de> for RawEnum {
+    type Error = Error;
+    type Variant = RawValue;
+
+    fn variant_seed<V: serde::de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, RawValue), Error> {
+        Ok((seed.deserialize(self.variant)?, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for RawValue {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self {
+            RawValue::Unit => Ok(()),
+            _ => Err(Error::custom("expected a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: serde::de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Shape {
+        Point,
+        Circle(f64),
+        Line(u32, u32),
+        Rect { w: u32, h: u32 },
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Meters(f64);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Tags(Vec<String>);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Entry {
+        name: String,
+        tags: Vec<String>,
+        size: Option<Meters>,
+        shape: Shape,
+    }
+
+    fn from_ron<T: DeserializeOwned>(s: &str) -> Result<T, Error> {
+        T::deserialize(ron::from_str::<RawValue>(s).unwrap())
+    }
+
+    fn from_json<T: DeserializeOwned>(s: &str) -> Result<T, Error> {
+        T::deserialize(serde_json::from_str::<RawValue>(s).unwrap())
+    }
+
+    #[test]
+    fn merges_nested_maps() {
+        let parent = from_ron::<RawValue>(r#"(a: 1, b: (c: 2, d: 3), e: [1, 2])"#).unwrap();
+        let child = from_ron::<RawValue>(r#"(b: (d: 4, f: 5), e: [3])"#).unwrap();
+        let expected = from_ron::<RawValue>(r#"(a: 1, b: (c: 2, d: 4, f: 5), e: [3])"#).unwrap();
+
+        assert_eq!(parent.merge(child), expected);
+    }
+
+    #[test]
+    fn merges_options() {
+        let parent = from_ron::<RawValue>(r#"(a: Some((b: 1, c: 2)))"#).unwrap();
+        let child = from_ron::<RawValue>(r#"(a: Some((c: 3)))"#).unwrap();
+        let expected = from_ron::<RawValue>(r#"(a: Some((b: 1, c: 3)))"#).unwrap();
+        assert_eq!(parent.merge(child), expected);
+
+        let parent = from_ron::<RawValue>(r#"(a: Some(1))"#).unwrap();
+        let child = from_ron::<RawValue>(r#"(a: None)"#).unwrap();
+        assert_eq!(parent.merge(child).get("a"), Some(&RawValue::None));
+    }
+
+    #[test]
+    fn round_trips_ron_entries() {
+        let entry: Entry = from_ron(
+            r#"(name: "a", tags: ["x", "y"], size: Some(Meters(1.5)), shape: {"Rect": (w: 1, h: 2)})"#,
+        )
+        .unwrap();
+        assert_eq!(
+            entry,
+            Entry {
+                name: "a".into(),
+                tags: vec!["x".into(), "y".into()],
+                size: Some(Meters(1.5)),
+                shape: Shape::Rect { w: 1, h: 2 },
+            }
+        );
+
+        let entry: Entry =
+            from_ron(r#"(name: "b", tags: [], size: None, shape: "Point")"#).unwrap();
+        assert_eq!(entry.size, None);
+        assert_eq!(entry.shape, Shape::Point);
+    }
+
+    #[test]
+    fn round_trips_json_entries() {
+        let entry: Entry =
+            from_json(r#"{"name": "a", "tags": ["x"], "size": 2.0, "shape": {"Line": [1, 2]}}"#)
+                .unwrap();
+        assert_eq!(entry.size, Some(Meters(2.0)));
+        assert_eq!(entry.shape, Shape::Line(1, 2));
+
+        let entry: Entry =
+            from_json(r#"{"name": "b", "tags": [], "size": null, "shape": {"Circle": 0.5}}"#)
+                .unwrap();
+        assert_eq!(entry.size, None);
+        assert_eq!(entry.shape, Shape::Circle(0.5));
+    }
+
+    #[test]
+    fn round_trips_newtypes() {
+        assert_eq!(from_ron::<Meters>("Meters(1.5)").unwrap(), Meters(1.5));
+        assert_eq!(from_ron::<Meters>("(1.5)").unwrap(), Meters(1.5));
+        assert_eq!(
+            from_ron::<Tags>(r#"Tags(["a", "b"])"#).unwrap(),
+            Tags(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(
+            from_ron::<Tags>(r#"Tags(["a"])"#).unwrap(),
+            Tags(vec!["a".into()])
+        );
+        assert_eq!(from_json::<Meters>("1.5").unwrap(), Meters(1.5));
+        assert_eq!(
+            from_json::<Tags>(r#"["a"]"#).unwrap(),
+            Tags(vec!["a".into()])
+        );
+        assert_eq!(
+            from_json::<Option<Tags>>(r#"["a", "b"]"#).unwrap(),
+            Some(Tags(vec!["a".into(), "b".into()]))
+        );
+    }
+
+    #[test]
+    fn round_trips_ron_enums() {
+        assert_eq!(from_ron::<Shape>(r#""Point""#).unwrap(), Shape::Point);
+        assert_eq!(
+            from_ron::<Shape>(r#"{"Circle": 2.0}"#).unwrap(),
+            Shape::Circle(2.0)
+        );
+        assert_eq!(
+            from_ron::<Shape>(r#"{"Line": (1, 2)}"#).unwrap(),
+            Shape::Line(1, 2)
+        );
+        assert_eq!(
+            from_ron::<Shape>(r#"{"Rect": (w: 1, h: 2)}"#).unwrap(),
+            Shape::Rect { w: 1, h: 2 }
+        );
+    }
+
+    #[test]
+    fn round_trips_json_enums() {
+        assert_eq!(from_json::<Shape>(r#""Point""#).unwrap(), Shape::Point);
+        assert_eq!(
+            from_json::<Shape>(r#"{"Circle": 2.0}"#).unwrap(),
+            Shape::Circle(2.0)
+        );
+        assert_eq!(
+            from_json::<Shape>(r#"{"Rect": {"w": 1, "h": 2}}"#).unwrap(),
+            Shape::Rect { w: 1, h: 2 }
+        );
+    }
+
+    #[test]
+    fn rejects_ron_variant_syntax() {
+        for s in [
+            "Point",
+            "Circle(2.0)",
+            "Line(1, 2)",
+            "Rect(w: 1, h: 2)",
+            "Circle(w: 1)",
+        ] {
+            let error = from_ron::<Shape>(s).unwrap_err();
+            assert!(
+                error.to_string().contains("RON's Variant(..)"),
+                "{s}: {error}"
+            );
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
+use std::any::{type_name, TypeId};
+
+/// A reference from one prototype to another, returned by [`Prototype::references`].
+///
+/// Once every manifest has been loaded, each reference is checked against the
+/// [`PrototypeLibrary`] of its target type, and a [`DanglingReference`] is sent if it doesn't exist.
+#[derive(Debug, Clone)]
+pub struct PrototypeReference {
+    field: &'static str,
+    target_type: TypeId,
+    target_type_name: &'static str,
+    target: u64,
+    target_name: Option<String>,
+}
+
+impl PrototypeReference {
+    /// Creates a reference from the field `field` to the prototype `target`
+    #[must_use]
+    pub fn new<T: Prototype>(field: &'static str, target: impl Into<IdRef<T>>) -> Self {
+        let target: IdRef<T> = target.into();
+        Self {
+            field,
+            target_type: TypeId::of::<T>(),
+            target_type_name: type_name::<T>(),
+            target: target.id().raw(),
+            target_name: target.name().map(str::to_string),
+        }
+    }
+}
+
+/// Sent for every reference to a prototype that doesn't exist once loading finishes
+#[derive(Event, Debug, Clone)]
+pub struct DanglingReference {
+    /// The type of the prototype holding the reference
+    pub source_type: &'static str,
+    /// The name of the prototype holding the reference
+    pub source: String,
+    /// The field the reference is stored in
+    pub field: &'static str,
+    /// The type of the prototype being referenced
+    pub target_type: &'static str,
+    /// The name of the prototype being referenced, or its raw id if the name isn't known
+    pub target: String,
+}
+
+type ContainsFn = fn(&World, u64) -> bool;
+type CollectFn = fn(&World) -> Vec<(String, PrototypeReference)>;
+
+/// The functions used to validate references for every registered prototype type
+#[derive(Resource, Default)]
+pub(crate) struct ReferenceValidators {
+    contains: HashMap<TypeId, ContainsFn>,
+    collect: HashMap<TypeId, (&'static str, CollectFn)>,
+}
+
+impl ReferenceValidators {
+    pub fn register<P: Prototype>(&mut self) {
+        self.contains.insert(TypeId::of::<P>(), |world, raw| {
+            world
+                .resource::<PrototypeLibrary<P>>()
+                .get(&Id::from_raw(raw))
+                .is_some()
+        });
+        self.collect.insert(
+            TypeId::of::<P>(),
+            (type_name::<P>(), |world| {
+                world
+                    .resource::<PrototypeLibrary<P>>()
+                    .iter()
+                    .flat_map(|(_, p)| {
+                        let name = p.name();
+                        p.references().into_iter().map(move |r| (name.clone(), r))
+                    })
+                    .collect()
+            }),
+        );
+    }
+
+    fn find_dangling(&self, world: &World) -> Vec<DanglingReference> {
+        let mut dangling = Vec::new();
+        for (source_type, collect) in self.collect.values() {
+            for (source, reference) in collect(world) {
+                let exists = self
+                    .contains
+                    .get(&reference.target_type)
+                    .is_some_and(|contains| contains(world, reference.target));
+                if exists {
+                    continue;
+                }
+
+                dangling.push(DanglingReference {
+                    source_type,
+                    source,
+                    field: reference.field,
+                    target_type: reference.target_type_name,
+                    target: reference
+                        .target_name
+                        .unwrap_or_else(|| reference.target.to_string()),
+                });
+            }
+        }
+
+        dangling
+    }
+}
+
+pub fn register_reference_validator<P: Prototype>(world: &mut World) {
+    world
+        .get_resource_or_insert_with(ReferenceValidators::default)
+        .register::<P>();
+}
+
+/// Checks every prototype's references once all manifests have been processed,
+/// and again whenever a manifest is reprocessed
+pub fn validate_references(world: &mut World) {
+    let Some(mut loader) = world.get_resource_mut::<ManifestLoader>() else {
+        return;
+    };
+    if !loader.take_validation_request() {
+        return;
+    }
+
+    let Some(validators) = world.get_resource::<ReferenceValidators>() else {
+        return;
+    };
+    let dangling = validators.find_dangling(world);
+    for reference in &dangling {
+        error!(
+            "Dangling reference: {} {:?} field {:?} references missing {} {:?}",
+            reference.source_type,
+            reference.source,
+            reference.field,
+            reference.target_type,
+            reference.target
+        );
+    }
+
+    world.send_event_batch(dangling);
+}
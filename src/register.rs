@@ -1,4 +1,10 @@
-use crate::prelude::*;
+#[cfg(feature = "json")]
+use crate::format_loaders::Json;
+#[cfg(any(feature = "ron", feature = "json"))]
+use crate::format_loaders::LocatedLoader;
+#[cfg(feature = "ron")]
+use crate::format_loaders::Ron;
+use crate::{load_report::ErrorLocations, prelude::*};
 use bevy::prelude::*;
 use serde::Deserialize;
 use std::path::PathBuf;
@@ -7,10 +13,46 @@ use std::path::PathBuf;
 pub trait RegisterPrototype {
     /// Add a prototype to the app. This will load the manifest,
     /// and insert the prototype into the PrototypeLibrary
+    ///
+    /// This can be called multiple times for the same manifest type, the
+    /// PrototypeLibrary will contain the prototypes from every file
     fn add_prototype<M: Manifest<Output = P>, P: Prototype>(
         &mut self,
         path: impl Into<PathBuf>,
     ) -> &mut Self;
+
+    /// Add every manifest in a folder (and its subfolders) to the app.
+    /// Only files with an extension matching the manifest's [`ManifestFormat`] are loaded.
+    ///
+    /// Each file is loaded as a manifest of type `M`, so a file that fails to load only fails itself.
+    ///
+    /// If 'hot_reload' is enabled, files added to or removed from the folder
+    /// will add or remove their prototypes
+    fn add_prototype_folder<M: Manifest<Output = P>, P: Prototype>(
+        &mut self,
+        path: impl Into<PathBuf>,
+    ) -> &mut Self;
+
+    /// Add every manifest in a folder to the app, ignoring subfolders.
+    /// See [`RegisterPrototype::add_prototype_folder`]
+    fn add_prototype_folder_shallow<M: Manifest<Output = P>, P: Prototype>(
+        &mut self,
+        path: impl Into<PathBuf>,
+    ) -> &mut Self;
+
+    /// Spawn prototypes of type `P` by cloning a template, instead of calling [`Prototype::build`] every time.
+    /// Each prototype is built once in an empty [`World`] when it's loaded, and the components it added
+    /// and the children it spawned are kept as its template. Templates are rebuilt whenever their prototype
+    /// changes, such as when it's hot reloaded.
+    ///
+    /// Templates are used when spawning by [`Id`], name or value, and in batches. A prototype spawned by value
+    /// only uses the template if its [`Prototype::content_hash`] matches the library's. Every component `build`
+    /// adds to the entity and its children must be registered with `#[reflect(Component)]`, and must not
+    /// reference other entities, otherwise the prototype is built every time. Since `build` runs in an empty
+    /// world, it can't read resources or other entities, and if it panics trying to the prototype is also built
+    /// every time. Cloning through reflection is slower than inserting components directly,
+    /// so this is only worth it for expensive builds
+    fn use_prototype_templates<P: Prototype>(&mut self) -> &mut Self;
 }
 
 impl RegisterPrototype for App {
@@ -18,62 +60,144 @@ impl RegisterPrototype for App {
         &mut self,
         path: impl Into<PathBuf>,
     ) -> &mut Self {
-        // Init Assets & Resources
-        self.init_asset::<M>();
-        self.init_resource::<PrototypeLibrary<P>>();
-        check_insert_loader(self.world_mut());
-        let mut loader = self.world_mut().resource_mut::<ManifestLoader>();
-        loader.register::<M>(path);
+        register_manifest::<M, P>(self, |loader| loader.register::<M>(path))
+    }
+
+    fn add_prototype_folder<M: Manifest<Output = P>, P: Prototype>(
+        &mut self,
+        path: impl Into<PathBuf>,
+    ) -> &mut Self {
+        register_manifest::<M, P>(self, |loader| loader.register_folder::<M>(path, true))
+    }
+
+    fn add_prototype_folder_shallow<M: Manifest<Output = P>, P: Prototype>(
+        &mut self,
+        path: impl Into<PathBuf>,
+    ) -> &mut Self {
+        register_manifest::<M, P>(self, |loader| loader.register_folder::<M>(path, false))
+    }
 
-        // Add child plugins for asset loading
-        add_plugins::<ManifestCollection<M>>(self);
+    fn use_prototype_templates<P: Prototype>(&mut self) -> &mut Self {
+        let enabled = self
+            .world_mut()
+            .get_resource_or_insert_with(PrototypeLibrary::<P>::new)
+            .enable_templates();
+        if !enabled {
+            return self;
+        }
 
-        // Add processing system
         self.add_systems(
             Update,
-            (load::<M>, track_asset::<M, P>)
-                .chain()
-                .in_set(ProtoSchedule::Loading),
-        );
+            update_templates::<P>
+                .after(ProtoSchedule::Loading)
+                .run_if(resource_changed::<PrototypeLibrary<P>>),
+        )
+    }
+}
 
-        // If hot reloading then register the rebuild system
-        #[cfg(feature = "hot_reload")]
-        register_rebuild_system::<P>(self.world_mut());
+fn register_manifest<M: Manifest<Output = P>, P: Prototype>(
+    app: &mut App,
+    register: impl FnOnce(&mut ManifestLoader),
+) -> &mut App {
+    // Init Assets & Resources
+    check_insert_loader(app.world_mut());
+    let mut loader = app.world_mut().resource_mut::<ManifestLoader>();
+    let is_registered = loader.is_registered::<M>();
+    loader.register_output::<M, P>();
+    register(&mut loader);
 
-        self
+    // Additional files for an existing manifest type only need their path
+    if is_registered {
+        return app;
     }
+
+    app.init_asset::<M>();
+    app.init_resource::<PrototypeLibrary<P>>();
+    app.init_resource::<ConflictPolicy>();
+    app.add_event::<PrototypeConflict<P>>();
+    app.add_event::<PrototypesChanged<P>>();
+    app.add_event::<EntitiesReloaded<P>>();
+    app.add_event::<EntitiesOrphaned<P>>();
+    app.add_event::<PrototypeReplaced<P>>();
+    app.init_resource::<ReloadSettings<P>>();
+    app.init_resource::<MissingPrototypePolicy<P>>();
+    app.add_event::<DanglingReference>();
+    app.add_event::<ManifestEntryError>();
+    app.add_event::<ManifestLoadFailed>();
+    app.init_resource::<ManifestLoadReport>();
+    register_reference_validator::<P>(app.world_mut());
+
+    // Add child plugins for asset loading
+    let locations = app.world().resource::<ManifestLoader>().error_locations();
+    add_plugins::<ManifestCollection<M>>(app, locations);
+
+    // Add processing system
+    app.add_systems(
+        Update,
+        (
+            load::<M>.in_set(ProtoSchedule::StartLoading),
+            track_folders::<M, P>,
+            track_asset::<M, P>,
+        )
+            .chain()
+            .in_set(ProtoSchedule::Loading),
+    );
+
+    // If hot reloading then register the rebuild system
+    #[cfg(feature = "hot_reload")]
+    register_rebuild_system::<P>(app.world_mut());
+
+    app
 }
 
-fn add_plugins<T: AccessManifestFormat + Clone + Asset + for<'de> Deserialize<'de>>(app: &mut App) {
+// `app`, `extensions` and `locations` are unused if every format feature is disabled
+#[allow(unused_variables)]
+fn add_plugins<T: AccessManifestFormat + Clone + Asset + for<'de> Deserialize<'de>>(
+    app: &mut App,
+    locations: ErrorLocations,
+) {
+    // Loaders are registered for their extensions so folders can load files untyped.
+    // RON and JSON have their own loaders, to record where parse errors are
+    let extensions = T::manifest_format().extensions();
     match T::manifest_format() {
         #[cfg(feature = "ron")]
         ManifestFormat::Ron => {
-            app.add_plugins(bevy_common_assets::ron::RonAssetPlugin::<T>::new(&[]));
+            app.init_asset::<T>()
+                .register_asset_loader(LocatedLoader::<T, Ron>::new(extensions, locations));
         }
         #[cfg(feature = "json")]
         ManifestFormat::Json => {
-            app.add_plugins(bevy_common_assets::json::JsonAssetPlugin::<T>::new(&[]));
+            app.init_asset::<T>()
+                .register_asset_loader(LocatedLoader::<T, Json>::new(extensions, locations));
         }
         #[cfg(feature = "yaml")]
         ManifestFormat::Yaml => {
-            app.add_plugins(bevy_common_assets::yaml::YamlAssetPlugin::<T>::new(&[]));
+            app.add_plugins(bevy_common_assets::yaml::YamlAssetPlugin::<T>::new(
+                extensions,
+            ));
         }
         #[cfg(feature = "toml")]
         ManifestFormat::Toml => {
-            app.add_plugins(bevy_common_assets::toml::TomlAssetPlugin::<T>::new(&[]));
+            app.add_plugins(bevy_common_assets::toml::TomlAssetPlugin::<T>::new(
+                extensions,
+            ));
         }
         #[cfg(feature = "csv")]
         ManifestFormat::Csv => {
-            app.add_plugins(bevy_common_assets::csv::CsvAssetPlugin::<T>::new(&[]));
+            app.add_plugins(bevy_common_assets::csv::CsvAssetPlugin::<T>::new(
+                extensions,
+            ));
         }
         #[cfg(feature = "xml")]
         ManifestFormat::Xml => {
-            app.add_plugins(bevy_common_assets::xml::XmlAssetPlugin::<T>::new(&[]));
+            app.add_plugins(bevy_common_assets::xml::XmlAssetPlugin::<T>::new(
+                extensions,
+            ));
         }
         #[cfg(feature = "msgpack")]
         ManifestFormat::MsgPack => {
             app.add_plugins(bevy_common_assets::msgpack::MsgPackAssetPlugin::<T>::new(
-                &[],
+                extensions,
             ));
         }
         ManifestFormat::Custom => (), // Users must register their own asset loader for custom formats.
@@ -0,0 +1,98 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// What hot reloading does to spawned entities when their prototype changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ReloadPolicy {
+    /// Calls [`Prototype::rebuild`] on the entity, which calls [`Prototype::build`] unless overridden
+    #[default]
+    Rebuild,
+    /// Calls [`Prototype::build`] on the entity, even if [`Prototype::rebuild`] is overridden
+    Build,
+    /// Despawns the entity's children and removes the components its prototype added, then builds it from scratch.
+    /// The entity keeps its id, transforms, parent and components added by anything else, such as gameplay
+    Respawn,
+    /// Leaves the entity unchanged, it's only listed in [`EntitiesReloaded`]
+    EventOnly,
+}
+
+/// What hot reloading does to spawned entities when their prototype is removed from its manifest
+#[derive(Debug, Clone, Default)]
+pub enum OrphanPolicy<P: Prototype> {
+    /// Despawns the entity and its children
+    Despawn,
+    /// Leaves the entity unchanged, and tags it with [`OrphanedPrototype`]
+    #[default]
+    Tag,
+    /// Applies the placeholder prototype with the [`ReloadPolicy`], and sets the entity's [`FromPrototype`] to it.
+    /// If the placeholder isn't in the library either, the entity is tagged with [`OrphanedPrototype`] instead
+    Fallback(Id<P>),
+}
+
+/// A marker for entities whose prototype was removed while hot reloading.
+/// It's removed if the prototype is added back
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrphanedPrototype;
+
+/// How hot reloading updates the entities spawned from prototypes of type `P`.
+/// Only used if 'hot_reload' is enabled
+#[derive(Resource, Debug, Clone)]
+pub struct ReloadSettings<P: Prototype> {
+    /// What is done to entities whose prototype changed
+    pub policy: ReloadPolicy,
+    /// What is done to entities whose prototype was removed
+    pub orphans: OrphanPolicy<P>,
+    /// Whether components the previous build added, and the new one doesn't, are removed.
+    /// Defaults to true for [`ReloadPolicy::Build`], and false for [`ReloadPolicy::Rebuild`],
+    /// as an overridden [`Prototype::rebuild`] may only update some components.
+    /// [`ReloadPolicy::Respawn`] always removes them
+    pub remove_stale_components: bool,
+}
+
+impl<P: Prototype> ReloadSettings<P> {
+    /// Creates settings with the given policy, and the default [`OrphanPolicy`]
+    pub fn new(policy: ReloadPolicy) -> Self {
+        Self {
+            policy,
+            orphans: OrphanPolicy::default(),
+            remove_stale_components: policy == ReloadPolicy::Build,
+        }
+    }
+
+    /// Sets whether components the previous build added, and the new one doesn't, are removed.
+    /// See [`ReloadSettings::remove_stale_components`]
+    pub fn with_stale_components_removed(mut self, remove: bool) -> Self {
+        self.remove_stale_components = remove;
+        self
+    }
+
+    /// Sets what is done to entities whose prototype was removed
+    pub fn with_orphans(mut self, orphans: OrphanPolicy<P>) -> Self {
+        self.orphans = orphans;
+        self
+    }
+}
+
+impl<P: Prototype> Default for ReloadSettings<P> {
+    fn default() -> Self {
+        Self::new(ReloadPolicy::default())
+    }
+}
+
+/// Sent when hot reloading handles entities whose prototype changed
+#[derive(Event, Debug, Clone)]
+pub struct EntitiesReloaded<P: Prototype> {
+    /// Each entity and the prototype it was spawned from
+    pub entities: Vec<(Entity, Id<P>)>,
+    /// The policy that was applied to the entities
+    pub policy: ReloadPolicy,
+}
+
+/// Sent when hot reloading removes prototypes that entities were spawned from
+#[derive(Event, Debug, Clone)]
+pub struct EntitiesOrphaned<P: Prototype> {
+    /// Each entity and the removed prototype it was spawned from
+    pub entities: Vec<(Entity, Id<P>)>,
+    /// The policy that was applied to the entities
+    pub policy: OrphanPolicy<P>,
+}
@@ -6,6 +6,10 @@ pub enum ProtoSchedule {
     /// The schedule for loading prototypes. If you want to control when
     /// prototypes are loaded, you can `configure_sets` on this schedule
     Loading,
+    /// The systems in [`ProtoSchedule::Loading`] that start loading registered manifests.
+    /// [`ProtoPlugin::with_loading_state`](crate::prelude::ProtoPlugin::with_loading_state)
+    /// only runs these in its loading state
+    StartLoading,
     /// The schedule for spawning prototypes whose spawn was requested
     /// asynchronously
     Spawning,
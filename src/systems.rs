@@ -1,7 +1,9 @@
 use crate::prelude::*;
 use bevy::{
+    asset::{AssetLoadFailedEvent, LoadedFolder},
+    ecs::system::SystemParam,
     prelude::*,
-    tasks::{block_on, poll_once},
+    state::state::FreelyMutableState,
 };
 #[cfg(feature = "progress_tracking")]
 use iyes_progress::Progress;
@@ -17,18 +19,31 @@ pub fn load<M: Manifest>(
     loader.load::<M>(&mut asset_server);
 }
 
+/// The resources needed to process manifest files as the asset server loads them
+#[derive(SystemParam)]
+pub struct ManifestEvents<'w, 's, M: Manifest<Output = P>, P: Prototype> {
+    events: EventReader<'w, 's, AssetEvent<ManifestCollection<M>>>,
+    assets: ResMut<'w, Assets<ManifestCollection<M>>>,
+    protos: ResMut<'w, PrototypeLibrary<P>>,
+    loader: ResMut<'w, ManifestLoader>,
+    policy: Res<'w, ConflictPolicy>,
+    conflicts: EventWriter<'w, PrototypeConflict<P>>,
+    report: ResMut<'w, ManifestLoadReport>,
+    entry_errors: EventWriter<'w, ManifestEntryError>,
+    failures: EventReader<'w, 's, AssetLoadFailedEvent<ManifestCollection<M>>>,
+    load_failed: EventWriter<'w, ManifestLoadFailed>,
+    changes: EventWriter<'w, PrototypesChanged<P>>,
+}
+
 #[cfg(feature = "hot_reload")]
 pub fn track_asset<M: Manifest<Output = P>, P: Prototype>(
     mut commands: Commands,
-    events: EventReader<AssetEvent<ManifestCollection<M>>>,
-    assets: ResMut<Assets<ManifestCollection<M>>>,
-    protos: ResMut<PrototypeLibrary<P>>,
-    loader: ResMut<ManifestLoader>,
+    manifests: ManifestEvents<M, P>,
     rebuild_systems: Res<RebuildSystems>,
 ) {
     use std::any::{type_name, TypeId};
 
-    if handle_asset_events(events, loader, assets, protos) {
+    if handle_asset_events(manifests) {
         let type_id = TypeId::of::<P>();
         let Some(system_id) = rebuild_systems.0.get(&type_id) else {
             error!("Rebuild system not registered for: {:?}", type_name::<P>());
@@ -39,31 +54,144 @@ pub fn track_asset<M: Manifest<Output = P>, P: Prototype>(
 }
 
 #[cfg(not(feature = "hot_reload"))]
-pub fn track_asset<M: Manifest<Output = P>, P: Prototype>(
-    events: EventReader<AssetEvent<ManifestCollection<M>>>,
-    assets: ResMut<Assets<ManifestCollection<M>>>,
-    protos: ResMut<PrototypeLibrary<P>>,
-    loader: ResMut<ManifestLoader>,
-) {
-    handle_asset_events(events, loader, assets, protos);
+pub fn track_asset<M: Manifest<Output = P>, P: Prototype>(manifests: ManifestEvents<M, P>) {
+    handle_asset_events(manifests);
 }
 
 fn handle_asset_events<M: Manifest<Output = P>, P: Prototype>(
-    mut events: EventReader<AssetEvent<ManifestCollection<M>>>,
-    mut loader: ResMut<ManifestLoader>,
-    mut assets: ResMut<Assets<ManifestCollection<M>>>,
-    mut protos: ResMut<PrototypeLibrary<P>>,
+    mut manifests: ManifestEvents<M, P>,
 ) -> bool {
-    let mut rebuilt = false;
+    let ManifestEvents {
+        events,
+        assets,
+        protos,
+        loader,
+        policy,
+        conflicts,
+        report,
+        entry_errors,
+        failures,
+        load_failed,
+        changes,
+    } = &mut manifests;
+
+    for ev in failures.read() {
+        if let Some(failure) = loader.fail::<M>(ev) {
+            report.add_failure(failure.clone());
+            load_failed.send(failure);
+        }
+    }
+
+    let mut loaded: Vec<AssetId<ManifestCollection<M>>> = loader.take_preloaded::<M>();
     for ev in events.read() {
-        if let AssetEvent::LoadedWithDependencies { id: _ } = ev {
-            loader.process::<M, P>(&mut assets, &mut protos);
+        // Manifests have no dependencies, so they're loaded again when they're modified
+        if let AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } = ev {
+            if !loaded.contains(id) {
+                loaded.push(*id);
+            }
+        }
+    }
 
-            rebuilt = true;
+    let mut changed = false;
+    for id in loaded {
+        if let Some(processed) = loader.process::<M, P>(id, assets, protos, **policy) {
+            conflicts.send_batch(processed.conflicts);
+            report.replace(&processed.paths, processed.errors.iter().cloned());
+            entry_errors.send_batch(processed.errors);
+            for failure in processed.failures {
+                report.add_failure(failure.clone());
+                load_failed.send(failure);
+            }
+            if !processed.changes.is_empty() {
+                changes.send(processed.changes);
+                changed = true;
+            }
         }
     }
 
-    rebuilt
+    changed
+}
+
+/// The resources needed to track the files in registered folders
+#[derive(SystemParam)]
+pub struct FolderEvents<'w, 's, P: Prototype> {
+    asset_server: Res<'w, AssetServer>,
+    events: EventReader<'w, 's, AssetEvent<LoadedFolder>>,
+    failures: EventReader<'w, 's, AssetLoadFailedEvent<LoadedFolder>>,
+    protos: ResMut<'w, PrototypeLibrary<P>>,
+    loader: ResMut<'w, ManifestLoader>,
+    report: ResMut<'w, ManifestLoadReport>,
+    load_failed: EventWriter<'w, ManifestLoadFailed>,
+    changes: EventWriter<'w, PrototypesChanged<P>>,
+}
+
+#[cfg(feature = "hot_reload")]
+pub fn track_folders<M: Manifest<Output = P>, P: Prototype>(
+    mut commands: Commands,
+    folders: FolderEvents<P>,
+    rebuild_systems: Res<RebuildSystems>,
+) {
+    use std::any::{type_name, TypeId};
+
+    if handle_folder_events::<M, P>(folders) {
+        let type_id = TypeId::of::<P>();
+        let Some(system_id) = rebuild_systems.0.get(&type_id) else {
+            error!("Rebuild system not registered for: {:?}", type_name::<P>());
+            return;
+        };
+        commands.run_system(*system_id);
+    }
+}
+
+#[cfg(not(feature = "hot_reload"))]
+pub fn track_folders<M: Manifest<Output = P>, P: Prototype>(folders: FolderEvents<P>) {
+    handle_folder_events::<M, P>(folders);
+}
+
+fn handle_folder_events<M: Manifest<Output = P>, P: Prototype>(
+    mut folders: FolderEvents<P>,
+) -> bool {
+    let FolderEvents {
+        asset_server,
+        events,
+        failures,
+        protos,
+        loader,
+        report,
+        load_failed,
+        changes,
+    } = &mut folders;
+
+    // Watched folders are reloaded when their files change, and fail if any file can't be
+    // loaded with the loader the asset server picks for it. Either way, the folder is scanned again
+    let changed: Vec<AssetId<LoadedFolder>> = events
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .chain(failures.read().map(|ev| ev.id))
+        .collect();
+    if changed.is_empty() && !loader.is_scanning::<M>() {
+        return false;
+    }
+
+    let result = loader.update_folders::<M, P>(asset_server, &changed, protos);
+    report.replace(&result.loaded, []);
+    for failure in result.failures {
+        error!(
+            "Failed to load manifest folder {} for {}: {}",
+            failure.path, failure.manifest, failure.message
+        );
+        report.add_failure(failure.clone());
+        load_failed.send(failure);
+    }
+
+    if result.changes.is_empty() {
+        return false;
+    }
+    changes.send(result.changes);
+    true
 }
 
 pub fn handle_async_spawn(
@@ -71,16 +199,41 @@ pub fn handle_async_spawn(
     mut load_tasks: Query<(Entity, &mut ProtoSpawnTask)>,
 ) {
     for (e, mut load_task) in load_tasks.iter_mut() {
-        if load_task.0.is_finished() {
-            if let Some(mut command_queue) = block_on(poll_once(&mut load_task.0)) {
-                commands.append(&mut command_queue);
-                commands.entity(e).despawn_recursive();
-            }
+        if let Some(build) = load_task.0.poll() {
+            // The entity may be despawned by an earlier command before this one is applied
+            commands.add(move |world: &mut World| {
+                if let Some(mut entity) = world.get_entity_mut(e) {
+                    entity.remove::<ProtoSpawnTask>();
+                    build(&mut entity);
+                }
+            });
         }
     }
 }
 
+/// Moves out of the loading state once every registered manifest has been processed or has failed
+pub fn finish_loading<S: FreelyMutableState>(
+    next: S,
+    failed: S,
+) -> impl FnMut(Res<ManifestLoader>, ResMut<NextState<S>>) {
+    move |loader, mut next_state| {
+        if !loader.is_all_processed() {
+            return;
+        }
+
+        if loader.is_any_failed() {
+            next_state.set(failed.clone());
+        } else {
+            next_state.set(next.clone());
+        }
+    }
+}
+
+pub fn update_progress(loader: Res<ManifestLoader>, mut progress: ResMut<ProtoProgress>) {
+    progress.set_if_neq(loader.progress());
+}
+
 #[cfg(feature = "progress_tracking")]
 pub fn track_progress(loader: Res<ManifestLoader>) -> Progress {
-    *loader.progress()
+    loader.progress().into()
 }
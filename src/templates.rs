@@ -0,0 +1,327 @@
+use crate::{prelude::*, prototype_components::build_tracked};
+use bevy::{
+    ecs::{
+        component::ComponentId,
+        reflect::{AppTypeRegistry, ReflectComponent, ReflectMapEntities},
+    },
+    prelude::*,
+    ptr::OwningPtr,
+    reflect::{ReflectFromReflect, TypeRegistry},
+    utils::HashMap,
+};
+use std::{
+    alloc::{self, Layout},
+    any::{type_name, TypeId},
+    fmt::{self, Debug},
+    panic::{self, AssertUnwindSafe},
+    ptr::NonNull,
+    sync::Arc,
+};
+
+/// A snapshot of the components a prototype's build adds, and of the children it spawns,
+/// which are cloned into entities through reflection instead of building them
+pub(crate) struct ComponentTemplate {
+    root: EntityTemplate,
+    name: String,
+    /// The [`Prototype::content_hash`] of the prototype the template was built from
+    hash: u64,
+    registry: AppTypeRegistry,
+}
+
+/// The components of one entity in a template, and the children spawned with it
+struct EntityTemplate {
+    components: Vec<TemplateComponent>,
+    children: Vec<EntityTemplate>,
+}
+
+/// A component in a template, and how to clone it into an entity
+struct TemplateComponent {
+    type_id: TypeId,
+    reflect: ReflectComponent,
+    /// Creates a concrete copy of the value to insert with the entity's other components
+    from_reflect: Option<ReflectFromReflect>,
+    value: Box<dyn Reflect>,
+}
+
+impl ComponentTemplate {
+    /// Builds the prototype on an entity in an empty world, and takes a snapshot of it and its children.
+    /// Returns `None` if any of their components isn't registered with `#[reflect(Component)]`
+    /// or references other entities, if the build spawned entities outside of its hierarchy,
+    /// or if it panicked, such as by reading a resource the empty world doesn't have
+    pub(crate) fn new<P: Prototype>(registry: &AppTypeRegistry, proto: &P) -> Option<Self> {
+        let mut scratch = World::new();
+        let root = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut target = scratch.spawn_empty();
+            build_prototype(&mut target, proto);
+            target.id()
+        }));
+        let Ok(root) = root else {
+            warn!(
+                "Building prototype {} of {} panicked in an empty world, it may read resources or other entities",
+                proto.name(),
+                type_name::<P>()
+            );
+            return None;
+        };
+
+        let orphaned = scratch
+            .iter_entities()
+            .any(|entity| entity.id() != root && !entity.contains::<Parent>());
+        if orphaned {
+            return None;
+        }
+
+        // The hierarchy is recorded by nesting children in the template instead
+        let hierarchy: Vec<ComponentId> = [
+            scratch.component_id::<Parent>(),
+            scratch.component_id::<Children>(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let tracking = scratch.get::<PrototypeComponents>(root)?;
+        let root = EntityTemplate::record(
+            &scratch,
+            &registry.read(),
+            root,
+            tracking.iter().collect(),
+            &hierarchy,
+        )?;
+
+        Some(Self {
+            root,
+            name: tracking.name().to_owned(),
+            hash: proto.content_hash(),
+            registry: registry.clone(),
+        })
+    }
+
+    /// Takes a snapshot of the components `proto` added or changed when it was built on the entity.
+    /// Returns `None` if any of them isn't registered with `#[reflect(Component)]`,
+    /// or references other entities, such as [`Children`]
+    pub(crate) fn snapshot<P: Prototype>(world: &World, entity: Entity, proto: &P) -> Option<Self> {
+        let registry = world.get_resource::<AppTypeRegistry>()?.clone();
+        let tracking = world.get::<PrototypeComponents>(entity)?;
+        let root = EntityTemplate::record(
+            world,
+            &registry.read(),
+            entity,
+            tracking.iter().collect(),
+            &[],
+        )?;
+
+        Some(Self {
+            root,
+            name: tracking.name().to_owned(),
+            hash: proto.content_hash(),
+            registry,
+        })
+    }
+
+    /// Returns true if the template was built from a prototype equal to `proto`
+    pub(crate) fn matches<P: Prototype>(&self, proto: &P) -> bool {
+        self.hash == proto.content_hash()
+    }
+
+    /// Clones the template's components into the entity, and spawns its children
+    pub(crate) fn apply(&self, target: &mut EntityWorldMut) {
+        self.root.apply(target, &self.registry.read());
+    }
+}
+
+impl EntityTemplate {
+    /// Takes a snapshot of the entity's `components` and its children's components,
+    /// except for the `hierarchy` components that link them
+    fn record(
+        world: &World,
+        types: &TypeRegistry,
+        entity: Entity,
+        components: Vec<ComponentId>,
+        hierarchy: &[ComponentId],
+    ) -> Option<Self> {
+        let source = world.entity(entity);
+        let components = components
+            .into_iter()
+            .filter(|id| !hierarchy.contains(id))
+            .map(|id| {
+                let type_id = world.components().get_info(id)?.type_id()?;
+                if types.get_type_data::<ReflectMapEntities>(type_id).is_some() {
+                    return None;
+                }
+                let reflect = types.get_type_data::<ReflectComponent>(type_id)?.clone();
+                let value = reflect.reflect(source)?.clone_value();
+                Some(TemplateComponent {
+                    type_id,
+                    reflect,
+                    from_reflect: types.get_type_data::<ReflectFromReflect>(type_id).cloned(),
+                    value,
+                })
+            })
+            .collect::<Option<_>>()?;
+
+        let children = source
+            .get::<Children>()
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|&child| {
+                        let components = world.entity(child).archetype().components().collect();
+                        Self::record(world, types, child, components, hierarchy)
+                    })
+                    .collect::<Option<_>>()
+            })
+            .unwrap_or(Some(Vec::new()))?;
+
+        Some(Self {
+            components,
+            children,
+        })
+    }
+
+    /// Inserts the components that can be copied concretely all at once, in a single archetype move,
+    /// and the rest one at a time through reflection
+    fn apply(&self, target: &mut EntityWorldMut, types: &TypeRegistry) {
+        let mut ids = Vec::with_capacity(self.components.len());
+        let mut values = Vec::with_capacity(self.components.len());
+        for component in &self.components {
+            match component.instance(target.world()) {
+                Some((id, value)) => {
+                    ids.push(id);
+                    values.push(value);
+                }
+                None => component
+                    .reflect
+                    .insert(target, component.value.as_ref(), types),
+            }
+        }
+        insert_boxed(target, &ids, values);
+
+        if !self.children.is_empty() {
+            target.with_children(|parent| {
+                for child in &self.children {
+                    child.apply(&mut parent.spawn_empty(), types);
+                }
+            });
+        }
+    }
+}
+
+impl TemplateComponent {
+    /// Creates a concrete copy of the value, and gets its component id in the world.
+    /// Returns `None` if the type can't be created from reflection or isn't a component in the world yet
+    fn instance(&self, world: &World) -> Option<(ComponentId, Box<dyn Reflect>)> {
+        let id = world.components().get_id(self.type_id)?;
+        let value = self
+            .from_reflect
+            .as_ref()?
+            .from_reflect(self.value.as_ref())?;
+        (value.as_any().type_id() == self.type_id).then_some((id, value))
+    }
+}
+
+/// Inserts the boxed components into the entity in a single archetype move.
+/// Each value must be of the component type of the id at the same index, from the entity's world
+#[allow(unsafe_code)]
+fn insert_boxed(target: &mut EntityWorldMut, ids: &[ComponentId], values: Vec<Box<dyn Reflect>>) {
+    if ids.is_empty() {
+        return;
+    }
+    let values: Vec<(NonNull<u8>, Layout)> = values
+        .into_iter()
+        .map(|value| {
+            let layout = Layout::for_value(value.as_ref());
+            (NonNull::from(Box::leak(value)).cast::<u8>(), layout)
+        })
+        .collect();
+
+    // SAFETY: each id is from the entity's world, and each pointer owns a value of that component's type,
+    // which `TemplateComponent::instance` checked. The values are moved into the entity,
+    // so only the boxes' memory is freed afterwards, without dropping the values
+    unsafe {
+        target.insert_by_ids(ids, values.iter().map(|&(ptr, _)| OwningPtr::new(ptr)));
+        for (ptr, layout) in values {
+            if layout.size() != 0 {
+                alloc::dealloc(ptr.as_ptr(), layout);
+            }
+        }
+    }
+}
+
+impl Debug for ComponentTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComponentTemplate")
+            .field("name", &self.name)
+            .field("components", &self.root.components.len())
+            .field("children", &self.root.children.len())
+            .finish()
+    }
+}
+
+/// Gets the template of the prototype `id`, if the library has one
+pub(crate) fn find_template<P: Prototype>(
+    world: &World,
+    id: Id<P>,
+) -> Option<Arc<ComponentTemplate>> {
+    world
+        .get_resource::<PrototypeLibrary<P>>()
+        .and_then(|protos| protos.template(&id))
+}
+
+/// Builds `proto` on the entity, cloning its template if the library has one built from an equal prototype.
+/// Prototypes spawned by value use the template too, unless they differ from the library's
+pub(crate) fn instantiate<P: Prototype>(target: &mut EntityWorldMut, proto: &P) {
+    let id = proto.id();
+    match find_template(target.world(), id).filter(|template| template.matches(proto)) {
+        Some(template) => {
+            target.insert(FromPrototype(id));
+            build_tracked(target, proto, false, |e| template.apply(e));
+        }
+        None => build_prototype(target, proto),
+    }
+}
+
+/// Builds templates for the prototypes that were added or changed since their template was last built,
+/// and drops the templates of prototypes that were removed. This runs whenever the library changes,
+/// so templates are refreshed by hot reloading in any state
+pub(crate) fn update_templates<P: Prototype>(
+    world: &mut World,
+    mut built: Local<HashMap<Id<P>, u64>>,
+) {
+    let Some(registry) = world.get_resource::<AppTypeRegistry>().cloned() else {
+        return;
+    };
+
+    let protos = world.resource::<PrototypeLibrary<P>>();
+    let removed: Vec<Id<P>> = built
+        .keys()
+        .filter(|id| protos.name(id).is_none())
+        .copied()
+        .collect();
+    let outdated: Vec<(Id<P>, u64, P)> = protos
+        .iter()
+        .map(|(id, proto)| (*id, proto.content_hash(), proto))
+        .filter(|(id, hash, _)| built.get(id) != Some(hash))
+        .map(|(id, hash, proto)| (id, hash, proto.clone()))
+        .collect();
+
+    // Templates aren't part of the library's contents, so they don't trigger this system again
+    let mut protos = world.resource_mut::<PrototypeLibrary<P>>();
+    let protos = protos.bypass_change_detection();
+    for id in removed {
+        built.remove(&id);
+        protos.set_template(id, None);
+    }
+
+    for (id, hash, proto) in outdated {
+        let template = ComponentTemplate::new(&registry, &proto);
+        if template.is_none() {
+            warn!(
+                "Prototype {} of {} can't be used as a template, it will be built on every spawn",
+                id.describe(),
+                type_name::<P>()
+            );
+        }
+        built.insert(id, hash);
+        protos.set_template(id, template);
+    }
+}
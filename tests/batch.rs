@@ -0,0 +1,177 @@
+//! Tests for spawning many instances of a prototype at once
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+struct SwarmManifest {
+    name: String,
+    children: bool,
+    reflected: bool,
+}
+
+impl Manifest for SwarmManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    type Output = Swarm;
+
+    fn reify(&self) -> Swarm {
+        Swarm {
+            name: self.name.clone(),
+            children: self.children,
+            reflected: self.reflected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct Swarm {
+    name: String,
+    children: bool,
+    reflected: bool,
+}
+
+/// Counts how many times a prototype was built
+#[derive(Resource, Default)]
+struct Builds(usize);
+
+/// A component that isn't registered for reflection
+#[derive(Component)]
+struct Opaque;
+
+#[derive(Component)]
+struct Bee;
+
+impl Prototype for Swarm {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn build(&self, target: &mut EntityWorldMut) {
+        target.world_scope(|world| world.resource_mut::<Builds>().0 += 1);
+        target.insert(Val(1));
+        if !self.reflected {
+            target.insert(Opaque);
+        }
+        if self.children {
+            target.with_children(|parent| {
+                parent.spawn(Bee);
+            });
+        }
+    }
+
+    fn content_hash(&self) -> u64 {
+        hash_of(self)
+    }
+}
+
+fn loaded_app(name: &str) -> App {
+    let (mut app, _) = app_with_files(
+        name,
+        &[(
+            "swarm.ron",
+            r#"(prototypes: [
+                (name: "Plain", children: false, reflected: true),
+                (name: "Opaque", children: false, reflected: false),
+                (name: "Hive", children: true, reflected: true),
+            ])"#,
+        )],
+        false,
+    );
+    app.register_type::<Val>();
+    app.init_resource::<Builds>();
+    app.add_prototype::<SwarmManifest, Swarm>("swarm.ron");
+    run_until_loaded::<Swarm>(&mut app, 3);
+    app
+}
+
+fn builds(app: &App) -> usize {
+    app.world().resource::<Builds>().0
+}
+
+#[test]
+fn builds_once_and_clones_into_the_rest() {
+    let mut app = loaded_app("batch_clone");
+    let entities = app
+        .world_mut()
+        .spawn_prototype_batch(Id::<Swarm>::from_name("Plain"), 5);
+
+    assert_eq!(builds(&app), 1);
+    assert_eq!(entities.len(), 5);
+    let tracking = app.world().get::<PrototypeComponents>(entities[0]).unwrap();
+    for &entity in &entities {
+        let entity = app.world().entity(entity);
+        assert_eq!(entity.get::<Val>(), Some(&Val(1)));
+        assert_eq!(
+            entity.get::<FromPrototype<Swarm>>().unwrap().0,
+            Id::from_name("Plain")
+        );
+        assert_eq!(
+            entity
+                .get::<PrototypeComponents>()
+                .unwrap()
+                .added()
+                .collect::<Vec<_>>(),
+            tracking.added().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn builds_every_instance_if_a_component_cant_be_cloned() {
+    let mut app = loaded_app("batch_opaque");
+    let entities = app
+        .world_mut()
+        .spawn_prototype_batch(Id::<Swarm>::from_name("Opaque"), 3);
+
+    assert_eq!(builds(&app), 3);
+    for entity in entities {
+        assert!(app.world().get::<Opaque>(entity).is_some());
+    }
+}
+
+#[test]
+fn builds_every_instance_if_it_has_children() {
+    let mut app = loaded_app("batch_children");
+    let entities = app
+        .world_mut()
+        .spawn_prototype_batch(Id::<Swarm>::from_name("Hive"), 3);
+
+    assert_eq!(builds(&app), 3);
+    let mut bees: Vec<Entity> = entities
+        .iter()
+        .flat_map(|&entity| app.world().get::<Children>(entity).unwrap().to_vec())
+        .collect();
+    bees.sort();
+    bees.dedup();
+    assert_eq!(bees.len(), 3);
+}
+
+#[test]
+fn inserts_overrides_after_building() {
+    let mut app = loaded_app("batch_overrides");
+    let entities = app
+        .world_mut()
+        .spawn_prototype_batch_with(Id::<Swarm>::from_name("Plain"), [Val(7), Val(8)]);
+
+    assert_eq!(builds(&app), 1);
+    assert_eq!(app.world().get::<Val>(entities[0]), Some(&Val(7)));
+    assert_eq!(app.world().get::<Val>(entities[1]), Some(&Val(8)));
+}
+
+#[test]
+fn skips_entities_despawned_before_the_batch_is_built() {
+    let mut app = loaded_app("batch_despawned");
+    let entities = app
+        .world_mut()
+        .commands()
+        .spawn_prototype_batch_with(Id::<Swarm>::from_name("Plain"), [Val(7), Val(8)]);
+    app.world_mut().despawn(entities[0]);
+    app.world_mut().flush();
+
+    assert!(app.world().get_entity(entities[0]).is_none());
+    assert_eq!(app.world().get::<Val>(entities[1]), Some(&Val(8)));
+}
@@ -0,0 +1,228 @@
+#![allow(dead_code)]
+use bevy::{prelude::*, state::app::StatesPlugin};
+use rantz_proto::prelude::*;
+use serde::Deserialize;
+use std::{
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+pub const ASSETS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets");
+
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+pub struct ItemManifest {
+    pub name: String,
+    pub i: i32,
+}
+
+impl Manifest for ItemManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    type Output = Item;
+
+    fn reify(&self) -> Item {
+        Item {
+            name: self.name.clone(),
+            i: self.i,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Item {
+    pub name: String,
+    pub i: i32,
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct Val(pub i32);
+
+impl Prototype for Item {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn build(&self, target: &mut EntityWorldMut) {
+        target.insert(Val(self.i));
+    }
+
+    fn content_hash(&self) -> u64 {
+        hash_of(self)
+    }
+}
+
+/// [`ItemManifest`], skipping invalid entries instead of rejecting their file
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+pub struct LenientManifest {
+    pub name: String,
+    pub i: i32,
+}
+
+impl Manifest for LenientManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    const LENIENT: bool = true;
+    type Output = Item;
+
+    fn reify(&self) -> Item {
+        Item {
+            name: self.name.clone(),
+            i: self.i,
+        }
+    }
+}
+
+/// A second manifest type in the same format as [`ItemManifest`], whose loot references items
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+pub struct MonsterManifest {
+    pub name: String,
+    pub loot: Vec<IdRef<Item>>,
+}
+
+impl Manifest for MonsterManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    type Output = Monster;
+
+    fn reify(&self) -> Monster {
+        Monster {
+            name: self.name.clone(),
+            loot: self.loot.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct Monster {
+    pub name: String,
+    pub loot: Vec<IdRef<Item>>,
+}
+
+impl Prototype for Monster {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn build(&self, _target: &mut EntityWorldMut) {}
+
+    fn references(&self) -> Vec<PrototypeReference> {
+        self.loot
+            .iter()
+            .map(|item| PrototypeReference::new("loot", item.clone()))
+            .collect()
+    }
+
+    fn content_hash(&self) -> u64 {
+        hash_of(self)
+    }
+}
+
+/// An app that loads assets from `dir`, without [`ProtoPlugin`]
+pub fn bare_app(dir: impl Into<String>, watch: bool) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        StatesPlugin,
+        AssetPlugin {
+            file_path: dir.into(),
+            watch_for_changes_override: Some(watch),
+            ..default()
+        },
+    ));
+    app
+}
+
+/// An app that loads assets from `dir`
+pub fn app_in(dir: impl Into<String>, watch: bool) -> App {
+    let mut app = bare_app(dir, watch);
+    app.add_plugins(ProtoPlugin::new());
+    app
+}
+
+/// An app that loads assets from the test assets folder
+pub fn app() -> App {
+    app_in(ASSETS, false)
+}
+
+/// An app that loads assets from a fresh folder, after writing each `(path, contents)` into it.
+/// Returns the folder, so tests can change the files
+pub fn app_with_files(name: &str, files: &[(&str, &str)], watch: bool) -> (App, PathBuf) {
+    let dir = temp_dir(name);
+    for (path, contents) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, contents);
+    }
+    (app_in(dir.to_string_lossy(), watch), dir)
+}
+
+/// Updates the app until `done` returns true, or gives up after a few seconds
+pub fn run_until(app: &mut App, mut done: impl FnMut(&mut App) -> bool) -> bool {
+    for _ in 0..1000 {
+        app.update();
+        if done(app) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    false
+}
+
+/// Updates the app until the library has `len` prototypes
+pub fn run_until_loaded<P: Prototype>(app: &mut App, len: usize) {
+    assert!(
+        run_until(app, |app| app
+            .world()
+            .resource::<PrototypeLibrary<P>>()
+            .len()
+            == len),
+        "library never had {len} prototypes"
+    );
+}
+
+/// A fresh folder for tests that write manifests, so hot reload can be observed
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rantz_proto_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes a file, waiting first so the file watcher sees it as a separate change
+pub fn write(path: impl AsRef<Path>, contents: &str) {
+    std::thread::sleep(Duration::from_millis(100));
+    std::fs::write(path, contents).unwrap();
+}
+
+/// Removes a file, waiting first so the file watcher doesn't merge it with the file being created
+pub fn remove(path: impl AsRef<Path>) {
+    std::thread::sleep(Duration::from_millis(500));
+    std::fs::remove_file(path).unwrap();
+}
+
+/// A hash of a value, for implementing `content_hash` with `Hash`
+pub fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Every load failure in the [`ManifestLoadReport`]
+pub fn failures(app: &App) -> Vec<ManifestLoadFailed> {
+    app.world()
+        .resource::<ManifestLoadReport>()
+        .failures()
+        .to_vec()
+}
+
+/// The entry and message of every entry error in the [`ManifestLoadReport`], sorted
+pub fn entry_errors(app: &App) -> Vec<(String, String)> {
+    let mut errors: Vec<_> = app
+        .world()
+        .resource::<ManifestLoadReport>()
+        .errors()
+        .iter()
+        .map(|e| (e.entry.clone(), e.message.clone()))
+        .collect();
+    errors.sort();
+    errors
+}
@@ -0,0 +1,227 @@
+//! Tests for manifest entries that map to the same id
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+
+#[derive(Resource, Default)]
+struct Conflicts(Vec<PrototypeConflict<Item>>);
+
+/// Loads `items/a.ron`, then `dup/x.ron` which redefines Foo
+fn load_duplicates(policy: ConflictPolicy) -> App {
+    let mut app = app();
+    app.insert_resource(policy);
+    app.init_resource::<Conflicts>();
+    app.add_systems(
+        Last,
+        |mut events: EventReader<PrototypeConflict<Item>>, mut conflicts: ResMut<Conflicts>| {
+            conflicts.0.extend(events.read().cloned());
+        },
+    );
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    run_until_loaded::<Item>(&mut app, 2);
+
+    app.add_prototype::<ItemManifest, Item>("dup/x.ron");
+    assert!(run_until(&mut app, |app| app
+        .world()
+        .resource::<ManifestLoader>()
+        .state::<ItemManifest>()
+        .is_some_and(|state| !matches!(
+            state,
+            ManifestState::Reloading
+        ))));
+    app.update();
+    app
+}
+
+fn foo_value(app: &App) -> Option<i32> {
+    app.world()
+        .resource::<PrototypeLibrary<Item>>()
+        .get_by_name("Foo")
+        .map(|item| item.i)
+}
+
+#[test]
+fn last_wins() {
+    let app = load_duplicates(ConflictPolicy::LastWins);
+
+    assert_eq!(foo_value(&app), Some(10));
+    assert_eq!(app.world().resource::<PrototypeLibrary<Item>>().len(), 3);
+    let conflicts = &app.world().resource::<Conflicts>().0;
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].kind, ConflictKind::DuplicateName);
+    assert_eq!(conflicts[0].existing_name, "Foo");
+    assert_eq!(
+        conflicts[0].existing_path.as_ref().unwrap().path(),
+        std::path::Path::new("items/a.ron")
+    );
+}
+
+#[test]
+fn first_wins() {
+    let app = load_duplicates(ConflictPolicy::FirstWins);
+
+    assert_eq!(foo_value(&app), Some(1));
+    assert_eq!(app.world().resource::<PrototypeLibrary<Item>>().len(), 3);
+    assert_eq!(app.world().resource::<Conflicts>().0.len(), 1);
+}
+
+#[test]
+fn errors_reject_the_file() {
+    let app = load_duplicates(ConflictPolicy::Error);
+
+    assert_eq!(foo_value(&app), Some(1));
+    let library = app.world().resource::<PrototypeLibrary<Item>>();
+    assert!(library.get_by_name("New").is_none());
+    assert_eq!(app.world().resource::<Conflicts>().0.len(), 1);
+    assert_eq!(failures(&app).len(), 1);
+    assert_eq!(failures(&app)[0].path, "dup/x.ron".into());
+}
+
+#[cfg(feature = "hot_reload")]
+mod hot_reload {
+    use super::*;
+
+    /// Two files which both define Foo
+    const FOO_TWICE: &[(&str, &str)] = &[
+        ("first/a.ron", r#"(prototypes: [(name: "Foo", i: 1)])"#),
+        (
+            "second/b.ron",
+            r#"(prototypes: [(name: "Foo", i: 2), (name: "Bar", i: 3)])"#,
+        ),
+    ];
+
+    /// Loads `first/a.ron` and then `second/b.ron`, which both define Foo,
+    /// then removes `remove_file` and returns Foo once it has changed
+    fn remove_definition(name: &str, policy: ConflictPolicy, remove_file: &str) -> i32 {
+        let (mut app, dir) = app_with_files(name, FOO_TWICE, true);
+        app.insert_resource(policy);
+        app.add_prototype_folder::<ItemManifest, Item>("first");
+        run_until_loaded::<Item>(&mut app, 1);
+        app.add_prototype_folder::<ItemManifest, Item>("second");
+        run_until_loaded::<Item>(&mut app, 2);
+
+        let before = foo_value(&app);
+        remove(dir.join(remove_file));
+        assert!(run_until(&mut app, |app| foo_value(app) != before));
+        foo_value(&app).unwrap()
+    }
+
+    #[test]
+    fn last_wins_falls_back_to_the_previous_definition() {
+        assert_eq!(
+            remove_definition(
+                "fallback_last_wins",
+                ConflictPolicy::LastWins,
+                "second/b.ron"
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn first_wins_falls_back_to_the_next_definition() {
+        assert_eq!(
+            remove_definition(
+                "fallback_first_wins",
+                ConflictPolicy::FirstWins,
+                "first/a.ron"
+            ),
+            2
+        );
+    }
+
+    /// Loads `first/a.ron` and then `second/b.ron`, which both define Foo,
+    /// then changes Foo in `edit_file` and returns Foo once the edit has been processed
+    fn reload_definition(name: &str, policy: ConflictPolicy, edit_file: &str) -> i32 {
+        let (mut app, dir) = app_with_files(name, FOO_TWICE, true);
+        app.insert_resource(policy);
+        app.add_prototype::<ItemManifest, Item>("first/a.ron");
+        run_until_loaded::<Item>(&mut app, 1);
+        app.add_prototype::<ItemManifest, Item>("second/b.ron");
+        run_until_loaded::<Item>(&mut app, 2);
+
+        write(
+            dir.join(edit_file),
+            r#"(prototypes: [(name: "Foo", i: 5), (name: "Baz", i: 4)])"#,
+        );
+        assert!(run_until(&mut app, |app| app
+            .world()
+            .resource::<PrototypeLibrary<Item>>()
+            .get_by_name("Baz")
+            .is_some()));
+        foo_value(&app).unwrap()
+    }
+
+    #[test]
+    fn first_wins_keeps_using_the_first_file_when_it_is_reloaded() {
+        assert_eq!(
+            reload_definition(
+                "reload_first_wins",
+                ConflictPolicy::FirstWins,
+                "first/a.ron"
+            ),
+            5
+        );
+    }
+
+    #[test]
+    fn first_wins_ignores_reloads_of_later_files() {
+        assert_eq!(
+            reload_definition(
+                "reload_first_wins_later",
+                ConflictPolicy::FirstWins,
+                "second/b.ron"
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn last_wins_ignores_reloads_of_earlier_files() {
+        assert_eq!(
+            reload_definition(
+                "reload_last_wins_earlier",
+                ConflictPolicy::LastWins,
+                "first/a.ron"
+            ),
+            2
+        );
+    }
+
+    #[test]
+    fn errors_keep_the_previous_prototypes_when_a_reload_conflicts() {
+        let (mut app, dir) = app_with_files(
+            "conflicts_error_reload",
+            &[
+                ("first/a.ron", r#"(prototypes: [(name: "Foo", i: 1)])"#),
+                ("second/b.ron", r#"(prototypes: [(name: "Bar", i: 3)])"#),
+            ],
+            true,
+        );
+        app.insert_resource(ConflictPolicy::Error);
+        app.add_prototype::<ItemManifest, Item>("first/a.ron");
+        app.add_prototype::<ItemManifest, Item>("second/b.ron");
+        run_until_loaded::<Item>(&mut app, 2);
+
+        write(
+            dir.join("second/b.ron"),
+            r#"(prototypes: [(name: "Foo", i: 2), (name: "Bar", i: 4)])"#,
+        );
+        assert!(run_until(&mut app, |app| !failures(app).is_empty()));
+        assert_eq!(failures(&app)[0].path, "second/b.ron".into());
+        let library = app.world().resource::<PrototypeLibrary<Item>>();
+        assert_eq!(library.get_by_name("Foo").unwrap().i, 1);
+        assert_eq!(library.get_by_name("Bar").unwrap().i, 3);
+
+        write(
+            dir.join("second/b.ron"),
+            r#"(prototypes: [(name: "Bar", i: 5)])"#,
+        );
+        assert!(run_until(&mut app, |app| failures(app).is_empty()));
+        let library = app.world().resource::<PrototypeLibrary<Item>>();
+        assert_eq!(library.get_by_name("Bar").unwrap().i, 5);
+    }
+}
@@ -0,0 +1,97 @@
+//! Tests for loading every manifest in a folder
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+
+fn names(app: &App) -> Vec<String> {
+    let mut names: Vec<String> = app
+        .world()
+        .resource::<PrototypeLibrary<Item>>()
+        .iter()
+        .map(|(_, item)| item.name.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+fn state(app: &App) -> Option<ManifestState> {
+    app.world()
+        .resource::<ManifestLoader>()
+        .state::<ItemManifest>()
+}
+
+#[test]
+fn loads_folders_recursively() {
+    let mut app = app();
+    app.add_prototype_folder::<ItemManifest, Item>("items");
+
+    assert!(run_until(&mut app, |app| state(app) == Some(ManifestState::Processed)));
+    assert_eq!(names(&app), ["Bar", "Baz", "Foo", "Qux"]);
+}
+
+#[test]
+fn ignores_subfolders_of_shallow_folders() {
+    let mut app = app();
+    app.add_prototype_folder_shallow::<ItemManifest, Item>("items");
+
+    assert!(run_until(&mut app, |app| state(app) == Some(ManifestState::Processed)));
+    assert_eq!(names(&app), ["Bar", "Baz", "Foo"]);
+}
+
+#[test]
+fn only_fails_the_files_that_fail() {
+    let (mut app, _) = app_with_files(
+        "folders_bad_file",
+        &[
+            ("a.ron", r#"(prototypes: [(name: "Foo", i: 1)])"#),
+            ("b.ron", r#"(prototypes: [(name: "Bar" i: 2)])"#),
+        ],
+        false,
+    );
+    app.add_prototype_folder::<ItemManifest, Item>("");
+
+    assert!(run_until(&mut app, |app| state(app) == Some(ManifestState::Failed)));
+    assert_eq!(names(&app), ["Foo"]);
+    let report = app.world().resource::<ManifestLoadReport>();
+    assert_eq!(report.failures().len(), 1);
+    assert_eq!(report.failures()[0].path, "b.ron".into());
+}
+
+#[test]
+fn loads_folders_next_to_other_manifests_of_the_same_format() {
+    let mut app = app();
+    app.add_prototype_folder::<ItemManifest, Item>("items");
+    app.add_prototype::<MonsterManifest, Monster>("refs/e.ron");
+
+    assert!(run_until(&mut app, |app| app
+        .world()
+        .resource::<ManifestLoader>()
+        .all_prototypes_ready()));
+    assert_eq!(state(&app), Some(ManifestState::Processed));
+    assert_eq!(names(&app), ["Bar", "Baz", "Foo", "Qux"]);
+    let monsters = app.world().resource::<PrototypeLibrary<Monster>>();
+    assert!(monsters.get_by_name("Goblin").is_some());
+}
+
+#[cfg(feature = "hot_reload")]
+#[test]
+fn tracks_files_added_to_and_removed_from_folders() {
+    let (mut app, dir) = app_with_files(
+        "folders_hot_reload",
+        &[("a.ron", r#"(prototypes: [(name: "Foo", i: 1)])"#)],
+        true,
+    );
+    app.add_prototype_folder::<ItemManifest, Item>("");
+    run_until_loaded::<Item>(&mut app, 1);
+
+    write(dir.join("b.ron"), r#"(prototypes: [(name: "Bar", i: 2)])"#);
+    run_until_loaded::<Item>(&mut app, 2);
+    assert_eq!(names(&app), ["Bar", "Foo"]);
+
+    remove(dir.join("a.ron"));
+    run_until_loaded::<Item>(&mut app, 1);
+    assert_eq!(names(&app), ["Bar"]);
+}
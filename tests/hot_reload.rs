@@ -0,0 +1,331 @@
+//! Tests for updating spawned entities when their prototypes are hot reloaded
+#![cfg(all(feature = "ron", feature = "hot_reload"))]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+use std::path::{Path, PathBuf};
+
+#[derive(Resource, Default)]
+struct Orphaned(Vec<Entity>);
+
+/// An app watching a fresh folder with `contents` in `a.ron`, and an entity spawned from Foo
+fn spawned_app(
+    name: &str,
+    contents: &str,
+    settings: ReloadSettings<Item>,
+) -> (App, PathBuf, Entity) {
+    let (mut app, dir) = app_with_files(name, &[("a.ron", contents)], true);
+    app.add_prototype::<ItemManifest, Item>("a.ron");
+    app.insert_resource(settings);
+    app.init_resource::<Orphaned>();
+    app.add_systems(
+        Last,
+        |mut events: EventReader<EntitiesOrphaned<Item>>, mut orphaned: ResMut<Orphaned>| {
+            for event in events.read() {
+                orphaned.0.extend(event.entities.iter().map(|(e, _)| *e));
+            }
+        },
+    );
+    assert!(run_until(&mut app, |app| app
+        .world()
+        .resource::<PrototypeLibrary<Item>>()
+        .get_id("Foo")
+        .is_some()));
+
+    let entity = app.world_mut().spawn_prototype_named::<Item>("Foo").id();
+    (app, dir, entity)
+}
+
+/// Removes Foo from the manifest, and waits for its entity to be handled
+fn orphan(app: &mut App, dir: &Path, entity: Entity) {
+    write(
+        dir.join("a.ron"),
+        r#"(prototypes: [(name: "Placeholder", i: 9)])"#,
+    );
+    assert!(run_until(app, |app| app
+        .world()
+        .resource::<Orphaned>()
+        .0
+        .contains(&entity)));
+    app.update();
+}
+
+const WITH_PLACEHOLDER: &str =
+    r#"(prototypes: [(name: "Foo", i: 1), (name: "Placeholder", i: 9)])"#;
+
+#[test]
+fn tags_orphans() {
+    let settings = ReloadSettings::default().with_orphans(OrphanPolicy::Tag);
+    let (mut app, dir, entity) = spawned_app("orphan_tag", WITH_PLACEHOLDER, settings);
+    orphan(&mut app, &dir, entity);
+
+    let entity = app.world().entity(entity);
+    assert!(entity.contains::<OrphanedPrototype>());
+    assert_eq!(entity.get::<Val>(), Some(&Val(1)));
+}
+
+#[test]
+fn despawns_orphans() {
+    let settings = ReloadSettings::default().with_orphans(OrphanPolicy::Despawn);
+    let (mut app, dir, entity) = spawned_app("orphan_despawn", WITH_PLACEHOLDER, settings);
+    orphan(&mut app, &dir, entity);
+
+    assert!(app.world().get_entity(entity).is_none());
+}
+
+#[test]
+fn falls_back_to_the_placeholder() {
+    let placeholder = Id::from_name("Placeholder");
+    let settings = ReloadSettings::default().with_orphans(OrphanPolicy::Fallback(placeholder));
+    let (mut app, dir, entity) = spawned_app("orphan_fallback", WITH_PLACEHOLDER, settings);
+    orphan(&mut app, &dir, entity);
+
+    let entity = app.world().entity(entity);
+    assert!(!entity.contains::<OrphanedPrototype>());
+    assert_eq!(entity.get::<Val>(), Some(&Val(9)));
+    assert_eq!(entity.get::<FromPrototype<Item>>().unwrap().0, placeholder);
+    assert_eq!(
+        entity.get::<PrototypeComponents>().unwrap().name(),
+        "Placeholder"
+    );
+}
+
+#[test]
+fn tags_orphans_if_the_placeholder_is_missing() {
+    let settings =
+        ReloadSettings::default().with_orphans(OrphanPolicy::Fallback(Id::from_name("Nobody")));
+    let (mut app, dir, entity) = spawned_app("orphan_no_fallback", WITH_PLACEHOLDER, settings);
+    orphan(&mut app, &dir, entity);
+
+    let entity = app.world().entity(entity);
+    assert!(entity.contains::<OrphanedPrototype>());
+    assert_eq!(entity.get::<Val>(), Some(&Val(1)));
+    assert_eq!(
+        entity.get::<FromPrototype<Item>>().unwrap().0,
+        Id::from_name("Foo")
+    );
+}
+
+#[derive(Component)]
+struct Health;
+
+/// Changes Foo in the manifest, and waits for its entity to be reloaded
+fn change(app: &mut App, dir: &Path) {
+    #[derive(Resource, Default)]
+    struct Reloaded(bool);
+
+    app.init_resource::<Reloaded>();
+    app.add_systems(
+        Last,
+        |mut events: EventReader<EntitiesReloaded<Item>>, mut reloaded: ResMut<Reloaded>| {
+            reloaded.0 |= events.read().count() > 0;
+        },
+    );
+    write(dir.join("a.ron"), r#"(prototypes: [(name: "Foo", i: 5)])"#);
+    assert!(run_until(app, |app| app.world().resource::<Reloaded>().0));
+    app.update();
+}
+
+const FOO: &str = r#"(prototypes: [(name: "Foo", i: 1)])"#;
+
+#[test]
+fn rebuilds_changed_prototypes() {
+    let settings = ReloadSettings::new(ReloadPolicy::Rebuild);
+    let (mut app, dir, entity) = spawned_app("reload_rebuild", FOO, settings);
+    app.world_mut().entity_mut(entity).insert(Health);
+    change(&mut app, &dir);
+
+    let entity = app.world().entity(entity);
+    assert_eq!(entity.get::<Val>(), Some(&Val(5)));
+    assert!(entity.contains::<Health>());
+}
+
+#[test]
+fn respawns_changed_prototypes_in_place() {
+    let settings = ReloadSettings::new(ReloadPolicy::Respawn);
+    let (mut app, dir, entity) = spawned_app("reload_respawn", FOO, settings);
+    let child = app.world_mut().spawn_empty().id();
+    app.world_mut()
+        .entity_mut(entity)
+        .insert((Health, Name::new("Foo"), Transform::from_xyz(1.0, 2.0, 3.0)))
+        .add_child(child);
+    change(&mut app, &dir);
+
+    assert!(app.world().get_entity(child).is_none());
+    let entity = app.world().entity(entity);
+    assert_eq!(entity.get::<Val>(), Some(&Val(5)));
+    assert!(entity.contains::<Health>());
+    assert_eq!(entity.get::<Name>().unwrap().as_str(), "Foo");
+    assert_eq!(
+        entity.get::<Transform>().unwrap().translation,
+        Vec3::new(1.0, 2.0, 3.0)
+    );
+}
+
+#[test]
+fn only_sends_events_for_changed_prototypes() {
+    let settings = ReloadSettings::new(ReloadPolicy::EventOnly);
+    let (mut app, dir, entity) = spawned_app("reload_event_only", FOO, settings);
+    change(&mut app, &dir);
+
+    assert_eq!(app.world().get::<Val>(entity), Some(&Val(1)));
+}
+
+#[test]
+fn only_rebuilds_entities_whose_prototype_changed() {
+    #[derive(Resource, Default)]
+    struct Seen {
+        changes: Vec<PrototypesChanged<Item>>,
+        reloaded: Vec<(Entity, Id<Item>)>,
+    }
+
+    let (mut app, dir, foo) = spawned_app(
+        "reload_granular",
+        r#"(prototypes: [(name: "Foo", i: 1), (name: "Bar", i: 2), (name: "Baz", i: 3)])"#,
+        ReloadSettings::new(ReloadPolicy::Rebuild),
+    );
+    let bar = app.world_mut().spawn_prototype_named::<Item>("Bar").id();
+    app.init_resource::<Seen>();
+    app.add_systems(
+        Last,
+        |mut changes: EventReader<PrototypesChanged<Item>>,
+         mut reloaded: EventReader<EntitiesReloaded<Item>>,
+         mut seen: ResMut<Seen>| {
+            seen.changes.extend(changes.read().cloned());
+            for event in reloaded.read() {
+                seen.reloaded.extend(event.entities.iter().copied());
+            }
+        },
+    );
+    // Events from loading the file are still buffered
+    app.update();
+    *app.world_mut().resource_mut::<Seen>() = Seen::default();
+    let bar_changed = app
+        .world()
+        .entity(bar)
+        .get_ref::<Val>()
+        .unwrap()
+        .last_changed();
+
+    write(
+        dir.join("a.ron"),
+        r#"(prototypes: [(name: "Foo", i: 5), (name: "Bar", i: 2), (name: "Qux", i: 4)])"#,
+    );
+    assert!(run_until(&mut app, |app| !app
+        .world()
+        .resource::<Seen>()
+        .reloaded
+        .is_empty()));
+    app.update();
+
+    let seen = app.world().resource::<Seen>();
+    assert_eq!(seen.changes.len(), 1);
+    assert_eq!(seen.changes[0].added, [Id::from_name("Qux")]);
+    assert_eq!(seen.changes[0].changed, [Id::from_name("Foo")]);
+    assert_eq!(seen.changes[0].removed, [Id::from_name("Baz")]);
+    assert_eq!(seen.reloaded, [(foo, Id::from_name("Foo"))]);
+
+    assert_eq!(app.world().get::<Val>(foo), Some(&Val(5)));
+    let bar = app.world().entity(bar).get_ref::<Val>().unwrap();
+    assert_eq!(*bar, Val(2));
+    assert_eq!(bar.last_changed(), bar_changed);
+}
+
+#[derive(Debug, Clone, serde::Deserialize, Asset, TypePath)]
+struct ChestManifest {
+    name: String,
+    i: i32,
+    locked: bool,
+}
+
+impl Manifest for ChestManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    type Output = Chest;
+
+    fn reify(&self) -> Chest {
+        Chest {
+            name: self.name.clone(),
+            i: self.i,
+            locked: self.locked,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct Chest {
+    name: String,
+    i: i32,
+    locked: bool,
+}
+
+#[derive(Component)]
+struct Locked;
+
+impl Prototype for Chest {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn build(&self, target: &mut EntityWorldMut) {
+        target.insert(Val(self.i));
+        if self.locked {
+            target.insert(Locked);
+        }
+    }
+
+    /// Only updates the value, so a chest unlocked while playing isn't locked again
+    fn rebuild(&self, target: &mut EntityWorldMut) {
+        target.insert(Val(self.i));
+    }
+
+    fn content_hash(&self) -> u64 {
+        hash_of(self)
+    }
+}
+
+/// Spawns a locked chest, then unlocks it in the manifest and returns the entity once it's reloaded
+fn unlock_chest(name: &str, settings: ReloadSettings<Chest>) -> (App, Entity) {
+    let (mut app, dir) = app_with_files(
+        name,
+        &[(
+            "a.ron",
+            r#"(prototypes: [(name: "Chest", i: 1, locked: true)])"#,
+        )],
+        true,
+    );
+    app.add_prototype::<ChestManifest, Chest>("a.ron");
+    app.insert_resource(settings);
+    run_until_loaded::<Chest>(&mut app, 1);
+    let entity = app.world_mut().spawn_prototype_named::<Chest>("Chest").id();
+    app.update();
+
+    write(
+        dir.join("a.ron"),
+        r#"(prototypes: [(name: "Chest", i: 5, locked: false)])"#,
+    );
+    assert!(run_until(&mut app, |app| app.world().get::<Val>(entity)
+        == Some(&Val(5))));
+    app.update();
+    (app, entity)
+}
+
+#[test]
+fn build_removes_stale_components_by_default() {
+    let (app, entity) = unlock_chest("stale_build", ReloadSettings::new(ReloadPolicy::Build));
+    assert!(!app.world().entity(entity).contains::<Locked>());
+}
+
+#[test]
+fn partial_rebuilds_keep_the_other_components_by_default() {
+    let (app, entity) = unlock_chest("stale_rebuild", ReloadSettings::default());
+    assert!(app.world().entity(entity).contains::<Locked>());
+}
+
+#[test]
+fn rebuilds_can_remove_stale_components() {
+    let settings = ReloadSettings::default().with_stale_components_removed(true);
+    let (app, entity) = unlock_chest("stale_rebuild_removed", settings);
+    assert!(!app.world().entity(entity).contains::<Locked>());
+}
@@ -0,0 +1,186 @@
+//! Tests for manifests whose entries inherit from other entries, and lenient manifests
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Hash, Deserialize)]
+struct Stats {
+    damage: u32,
+    weight: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Deserialize)]
+enum Element {
+    Fire,
+    Ice(u32),
+}
+
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+struct SwordManifest {
+    name: String,
+    stats: Stats,
+    element: Element,
+}
+
+impl Manifest for SwordManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    const INHERITANCE: bool = true;
+    const LENIENT: bool = true;
+    type Output = Sword;
+
+    fn reify(&self) -> Sword {
+        Sword {
+            name: self.name.clone(),
+            stats: self.stats.clone(),
+            element: self.element.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Hash)]
+struct Sword {
+    name: String,
+    stats: Stats,
+    element: Element,
+}
+
+impl Prototype for Sword {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn build(&self, target: &mut EntityWorldMut) {
+        target.insert(Val(self.stats.damage as i32));
+    }
+
+    fn content_hash(&self) -> u64 {
+        hash_of(self)
+    }
+}
+
+fn sword(app: &App, name: &str) -> Option<Sword> {
+    app.world()
+        .resource::<PrototypeLibrary<Sword>>()
+        .get_by_name(name)
+}
+
+#[test]
+fn merges_entries_over_their_parents_across_files() {
+    let mut app = app();
+    app.add_prototype_folder::<SwordManifest, Sword>("inh");
+    run_until_loaded::<Sword>(&mut app, 2);
+    app.update();
+
+    assert_eq!(
+        sword(&app, "iron_sword").unwrap().stats,
+        Stats {
+            damage: 3,
+            weight: 5
+        }
+    );
+    let steel = sword(&app, "steel_sword").unwrap();
+    assert_eq!(
+        steel.stats,
+        Stats {
+            damage: 3,
+            weight: 7
+        }
+    );
+    assert_eq!(steel.element, Element::Ice(2));
+    assert!(sword(&app, "sword").is_none());
+}
+
+#[test]
+fn reports_missing_parents_and_cycles() {
+    let mut app = app();
+    app.add_prototype_folder::<SwordManifest, Sword>("inh");
+    run_until_loaded::<Sword>(&mut app, 2);
+    assert!(run_until(&mut app, |app| entry_errors(app).len() == 3));
+
+    let errors = entry_errors(&app);
+    assert_eq!(errors[0].0, "loop_a");
+    assert!(errors[0].1.contains("cyclic"), "{}", errors[0].1);
+    assert_eq!(errors[1].0, "loop_b");
+    assert_eq!(errors[2].0, "orphan");
+    assert!(errors[2].1.contains("\"nobody\""), "{}", errors[2].1);
+}
+
+#[cfg(feature = "hot_reload")]
+mod hot_reload {
+    use super::*;
+
+    const BASE: &str = r#"(prototypes: [
+        (name: "sword", abstract: true, stats: (damage: 1, weight: 5), element: "Fire"),
+    ])"#;
+    const SWORDS: &str = r#"(prototypes: [
+        (name: "iron_sword", parent: "sword", stats: (damage: 3)),
+        (name: "heavy_sword", parent: "iron_sword", stats: (weight: 9)),
+    ])"#;
+
+    /// An app watching a fresh folder with the base sword and swords inheriting from it
+    fn swords_app(name: &str) -> (App, std::path::PathBuf) {
+        let (mut app, dir) =
+            app_with_files(name, &[("base.ron", BASE), ("swords.ron", SWORDS)], true);
+        app.add_prototype::<SwordManifest, Sword>("base.ron");
+        app.add_prototype::<SwordManifest, Sword>("swords.ron");
+        run_until_loaded::<Sword>(&mut app, 2);
+        (app, dir)
+    }
+
+    #[test]
+    fn resolves_children_again_when_a_parent_changes() {
+        let (mut app, dir) = swords_app("inherit_reload");
+        write(
+            dir.join("base.ron"),
+            r#"(prototypes: [(name: "sword", abstract: true, stats: (damage: 1, weight: 6), element: {"Ice": 1})])"#,
+        );
+
+        assert!(run_until(&mut app, |app| sword(app, "heavy_sword")
+            .is_some_and(|s| s.element == Element::Ice(1))));
+        let iron = sword(&app, "iron_sword").unwrap();
+        assert_eq!(
+            iron.stats,
+            Stats {
+                damage: 3,
+                weight: 6
+            }
+        );
+        assert_eq!(iron.element, Element::Ice(1));
+    }
+
+    #[test]
+    fn rejects_parents_defined_more_than_once() {
+        let (mut app, dir) = swords_app("inherit_ambiguous");
+        write(dir.join("other.ron"), BASE);
+        app.add_prototype::<SwordManifest, Sword>("other.ron");
+
+        assert!(run_until(&mut app, |app| sword(app, "iron_sword").is_none()));
+        let errors = entry_errors(&app);
+        assert_eq!(errors[0].0, "heavy_sword");
+        assert_eq!(errors[1].0, "iron_sword");
+        assert!(
+            errors[1].1.contains("defined by 2 entries"),
+            "{}",
+            errors[1].1
+        );
+    }
+}
+
+#[test]
+fn lenient_manifests_skip_invalid_entries() {
+    let mut app = app();
+    app.add_prototype::<LenientManifest, Item>("len/x.ron");
+    run_until_loaded::<Item>(&mut app, 2);
+    app.update();
+
+    let library = app.world().resource::<PrototypeLibrary<Item>>();
+    assert_eq!(library.get_by_name("C").unwrap().i, -5);
+    assert!(library.get_by_name("B").is_none());
+    let errors = entry_errors(&app);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "B");
+}
@@ -0,0 +1,208 @@
+//! Tests for the load failures and entry errors collected in `ManifestLoadReport`
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+
+#[test]
+fn reports_where_files_failed_to_parse() {
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("bad/x.ron");
+    assert!(run_until(&mut app, |app| !failures(app).is_empty()));
+
+    let failure = &failures(&app)[0];
+    assert_eq!(failure.path, "bad/x.ron".into());
+    assert_eq!(failure.format, ManifestFormat::Ron);
+    assert_eq!((failure.line, failure.column), (Some(3), Some(14)));
+}
+
+#[test]
+fn reports_where_files_in_folders_failed_to_parse() {
+    let mut app = app();
+    app.add_prototype_folder::<ItemManifest, Item>("bad");
+    assert!(run_until(&mut app, |app| !failures(app).is_empty()));
+
+    let failure = &failures(&app)[0];
+    assert_eq!(failure.path, "bad/x.ron".into());
+    assert_eq!((failure.line, failure.column), (Some(3), Some(14)));
+}
+
+#[test]
+fn leaves_folder_failures_without_a_location() {
+    let mut app = app();
+    app.add_prototype_folder::<ItemManifest, Item>("nowhere");
+    assert!(run_until(&mut app, |app| !failures(app).is_empty()));
+
+    let failure = &failures(&app)[0];
+    assert_eq!(failure.path, "nowhere".into());
+    assert_eq!((failure.line, failure.column), (None, None));
+}
+
+#[test]
+fn reports_entry_errors_per_file() {
+    let mut app = app();
+    app.add_prototype::<LenientManifest, Item>("len/x.ron");
+    app.add_prototype::<LenientManifest, Item>("items/a.ron");
+    run_until_loaded::<Item>(&mut app, 4);
+    app.update();
+
+    let report = app.world().resource::<ManifestLoadReport>();
+    assert!(!report.is_ok());
+    let path = "len/x.ron".into();
+    let errors: Vec<_> = report.errors_for(&path).collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].entry, "B");
+    assert_eq!(report.errors_for(&"items/a.ron".into()).count(), 0);
+}
+
+#[cfg(feature = "hot_reload")]
+#[test]
+fn clears_failures_when_the_file_is_fixed() {
+    let (mut app, dir) = app_with_files(
+        "load_report_fixed",
+        &[("a.ron", r#"(prototypes: [(name: "Foo" i: 1)])"#)],
+        true,
+    );
+    app.add_prototype::<ItemManifest, Item>("a.ron");
+    assert!(run_until(&mut app, |app| !failures(app).is_empty()));
+    assert_eq!(failures(&app)[0].line, Some(1));
+
+    write(dir.join("a.ron"), r#"(prototypes: [(name: "Foo", i: 1)])"#);
+    run_until_loaded::<Item>(&mut app, 1);
+    assert!(app.world().resource::<ManifestLoadReport>().is_ok());
+}
+
+/// [`ItemManifest`], rejecting the whole file if an item has a negative value
+#[derive(Debug, Clone, serde::Deserialize, Asset, TypePath)]
+struct StrictManifest {
+    name: String,
+    i: i32,
+}
+
+impl Manifest for StrictManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    type Output = Item;
+
+    fn reify(&self) -> Item {
+        Item {
+            name: self.name.clone(),
+            i: self.i,
+        }
+    }
+
+    fn try_reify(&self) -> Result<Item, ReifyError> {
+        if self.i < 0 {
+            return Err("negative value".into());
+        }
+        Ok(self.reify())
+    }
+}
+
+fn foo(app: &App) -> Option<i32> {
+    app.world()
+        .resource::<PrototypeLibrary<Item>>()
+        .get_by_name("Foo")
+        .map(|item| item.i)
+}
+
+#[test]
+fn reports_rejected_files_as_failures() {
+    let (mut app, _) = app_with_files(
+        "load_report_rejected",
+        &[(
+            "a.ron",
+            r#"(prototypes: [(name: "Foo", i: 1), (name: "Bar", i: -1)])"#,
+        )],
+        false,
+    );
+    app.add_prototype::<StrictManifest, Item>("a.ron");
+    assert!(run_until(&mut app, |app| !failures(app).is_empty()));
+
+    assert_eq!(foo(&app), None);
+    assert_eq!(failures(&app)[0].path, "a.ron".into());
+    assert_eq!(entry_errors(&app).len(), 1);
+    assert_eq!(
+        app.world()
+            .resource::<ManifestLoader>()
+            .state::<StrictManifest>(),
+        Some(ManifestState::Failed)
+    );
+}
+
+#[cfg(feature = "hot_reload")]
+#[test]
+fn keeps_the_previous_prototypes_when_a_reload_is_rejected() {
+    let (mut app, dir) = app_with_files(
+        "load_report_rejected_reload",
+        &[("a.ron", r#"(prototypes: [(name: "Foo", i: 1)])"#)],
+        true,
+    );
+    app.add_prototype::<StrictManifest, Item>("a.ron");
+    run_until_loaded::<Item>(&mut app, 1);
+
+    write(
+        dir.join("a.ron"),
+        r#"(prototypes: [(name: "Foo", i: 2), (name: "Bar", i: -1)])"#,
+    );
+    assert!(run_until(&mut app, |app| !failures(app).is_empty()));
+    assert_eq!(foo(&app), Some(1));
+    assert_eq!(app.world().resource::<PrototypeLibrary<Item>>().len(), 1);
+
+    write(
+        dir.join("a.ron"),
+        r#"(prototypes: [(name: "Foo", i: 3), (name: "Bar", i: 1)])"#,
+    );
+    run_until_loaded::<Item>(&mut app, 2);
+    assert_eq!(foo(&app), Some(3));
+    assert!(app.world().resource::<ManifestLoadReport>().is_ok());
+    assert_eq!(
+        app.world()
+            .resource::<ManifestLoader>()
+            .state::<StrictManifest>(),
+        Some(ManifestState::Processed)
+    );
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+    struct JsonItemManifest {
+        name: String,
+        i: i32,
+    }
+
+    impl Manifest for JsonItemManifest {
+        const FORMAT: ManifestFormat = ManifestFormat::Json;
+        type Output = Item;
+
+        fn reify(&self) -> Item {
+            Item {
+                name: self.name.clone(),
+                i: self.i,
+            }
+        }
+    }
+
+    #[test]
+    fn reports_where_json_files_failed_to_parse() {
+        let (mut app, _) = app_with_files(
+            "load_report_json",
+            &[(
+                "a.json",
+                "{\"prototypes\": [\n  {\"name\": \"a\", \"i\": 1},\n  {\"name\": \"b\" \"i\": 2}\n]}",
+            )],
+            false,
+        );
+        app.add_prototype::<JsonItemManifest, Item>("a.json");
+        assert!(run_until(&mut app, |app| !failures(app).is_empty()));
+
+        let failure = &failures(&app)[0];
+        assert_eq!(failure.format, ManifestFormat::Json);
+        assert_eq!((failure.line, failure.column), (Some(3), Some(16)));
+    }
+}
@@ -0,0 +1,78 @@
+//! Tests for `ProtoPlugin::with_loading_state`
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+enum GameState {
+    #[default]
+    Loading,
+    Menu,
+    Broken,
+}
+
+fn app_with_state(dir: impl Into<String>, watch: bool) -> App {
+    let mut app = bare_app(dir, watch);
+    app.add_plugins(ProtoPlugin::new().with_loading_state(
+        GameState::Loading,
+        GameState::Menu,
+        GameState::Broken,
+    ));
+    app
+}
+
+fn state(app: &App) -> GameState {
+    app.world().resource::<State<GameState>>().get().clone()
+}
+
+#[test]
+fn moves_to_next_state_once_loaded() {
+    let mut app = app_with_state(ASSETS, false);
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+
+    assert!(run_until(&mut app, |app| state(app) != GameState::Loading));
+    assert_eq!(state(&app), GameState::Menu);
+    assert_eq!(app.world().resource::<PrototypeLibrary<Item>>().len(), 2);
+}
+
+#[test]
+fn moves_to_failed_state_if_a_manifest_fails() {
+    let mut app = app_with_state(ASSETS, false);
+    app.add_prototype::<ItemManifest, Item>("bad/x.ron");
+
+    assert!(run_until(&mut app, |app| state(app) != GameState::Loading));
+    assert_eq!(state(&app), GameState::Broken);
+}
+
+#[test]
+fn doesnt_load_outside_of_the_loading_state() {
+    let mut app = app_with_state(ASSETS, false);
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(app.world().resource::<PrototypeLibrary<Item>>().is_empty());
+}
+
+#[cfg(feature = "hot_reload")]
+#[test]
+fn hot_reloads_after_leaving_the_loading_state() {
+    let dir = temp_dir("loading_state_hot_reload");
+    write(dir.join("a.ron"), r#"(prototypes: [(name: "Foo", i: 1)])"#);
+    let mut app = app_with_state(dir.to_string_lossy(), true);
+    app.add_prototype::<ItemManifest, Item>("a.ron");
+    assert!(run_until(&mut app, |app| state(app) == GameState::Menu));
+
+    write(
+        dir.join("a.ron"),
+        r#"(prototypes: [(name: "Foo", i: 1), (name: "Bar", i: 2)])"#,
+    );
+    run_until_loaded::<Item>(&mut app, 2);
+}
@@ -0,0 +1,110 @@
+//! Tests for the load state of manifests, and the run conditions that use it
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+
+fn state(app: &App) -> Option<ManifestState> {
+    app.world()
+        .resource::<ManifestLoader>()
+        .state::<ItemManifest>()
+}
+
+#[test]
+fn moves_from_registered_to_processed() {
+    let mut app = app();
+    assert_eq!(state(&app), None);
+
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    assert_eq!(state(&app), Some(ManifestState::Registered));
+
+    app.update();
+    assert_eq!(state(&app), Some(ManifestState::Loading));
+
+    assert!(run_until(&mut app, |app| state(app) == Some(ManifestState::Processed)));
+    assert!(app
+        .world()
+        .resource::<ManifestLoader>()
+        .prototypes_ready::<Item>());
+}
+
+#[test]
+fn fails_if_a_file_fails() {
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    app.add_prototype::<ItemManifest, Item>("bad/x.ron");
+
+    assert!(run_until(&mut app, |app| state(app) == Some(ManifestState::Failed)));
+    assert_eq!(app.world().resource::<PrototypeLibrary<Item>>().len(), 2);
+}
+
+#[test]
+fn runs_systems_once_prototypes_are_ready() {
+    #[derive(Resource, Default)]
+    struct Seen(Option<usize>);
+
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    app.init_resource::<Seen>();
+    app.add_systems(
+        Update,
+        (|protos: Res<PrototypeLibrary<Item>>, mut seen: ResMut<Seen>| {
+            seen.0.get_or_insert(protos.len());
+        })
+        .after(ProtoSchedule::Loading)
+        .run_if(prototypes_ready::<Item>()),
+    );
+
+    assert!(run_until(&mut app, |app| app
+        .world()
+        .resource::<Seen>()
+        .0
+        .is_some()));
+    assert_eq!(app.world().resource::<Seen>().0, Some(2));
+}
+
+#[test]
+fn reloads_files_registered_after_it_was_processed() {
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    assert!(run_until(&mut app, |app| state(app) == Some(ManifestState::Processed)));
+
+    app.add_prototype::<ItemManifest, Item>("items/b.ron");
+    assert_eq!(state(&app), Some(ManifestState::Reloading));
+    assert!(app
+        .world()
+        .resource::<ManifestLoader>()
+        .prototypes_ready::<Item>());
+
+    assert!(run_until(&mut app, |app| state(app) == Some(ManifestState::Processed)));
+    assert_eq!(app.world().resource::<PrototypeLibrary<Item>>().len(), 3);
+}
+
+#[cfg(feature = "hot_reload")]
+#[test]
+fn processes_modified_files() {
+    let (mut app, dir) = app_with_files(
+        "manifest_state_reload",
+        &[("a.ron", r#"(prototypes: [(name: "Foo", i: 1)])"#)],
+        true,
+    );
+    app.add_prototype::<ItemManifest, Item>("a.ron");
+    assert!(run_until(&mut app, |app| state(app) == Some(ManifestState::Processed)));
+
+    // Modified files are processed in the update the asset server finishes reloading them,
+    // so the manifest stays processed the whole time
+    write(dir.join("a.ron"), r#"(prototypes: [(name: "Foo", i: 2)])"#);
+    let mut states = Vec::new();
+    assert!(run_until(&mut app, |app| {
+        states.push(state(app));
+        app.world()
+            .resource::<PrototypeLibrary<Item>>()
+            .get_by_name("Foo")
+            .is_some_and(|item| item.i == 2)
+    }));
+    assert!(states
+        .iter()
+        .all(|state| *state == Some(ManifestState::Processed)));
+}
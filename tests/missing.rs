@@ -0,0 +1,88 @@
+//! Tests for `MissingPrototypePolicy`
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+
+/// An app that has loaded `Foo` and `Bar`, with the given policy for missing items
+fn app_with_policy(policy: MissingPrototypePolicy<Item>) -> App {
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    app.insert_resource(policy);
+    run_until_loaded::<Item>(&mut app, 2);
+    app
+}
+
+#[test]
+fn logs_and_leaves_the_entity_empty_by_default() {
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    run_until_loaded::<Item>(&mut app, 2);
+
+    let entity = app.world_mut().spawn_prototype_named::<Item>("Nope").id();
+    assert!(app.world().get::<Val>(entity).is_none());
+    assert!(app.world().get::<FromPrototype<Item>>(entity).is_none());
+}
+
+#[test]
+fn leaves_entities_spawned_with_commands_empty() {
+    let mut app = app_with_policy(MissingPrototypePolicy::Log);
+    let entity = app
+        .world_mut()
+        .commands()
+        .spawn_prototype_named::<Item>("Nope")
+        .id();
+    app.world_mut().flush();
+
+    assert!(app.world().get_entity(entity).is_some());
+    assert!(app.world().get::<Val>(entity).is_none());
+}
+
+#[test]
+#[should_panic(expected = "not found")]
+fn panics_with_the_panic_policy() {
+    let mut app = app_with_policy(MissingPrototypePolicy::Panic);
+    app.world_mut().spawn_prototype_named::<Item>("Nope");
+}
+
+#[test]
+fn builds_the_placeholder_instead() {
+    let mut app = app_with_policy(MissingPrototypePolicy::Placeholder(Id::from_name("Foo")));
+    let entity = app.world_mut().spawn_prototype_named::<Item>("Nope").id();
+
+    assert_eq!(app.world().get::<Val>(entity), Some(&Val(1)));
+    assert_eq!(
+        app.world().get::<FromPrototype<Item>>(entity).unwrap().0,
+        Id::from_name("Foo")
+    );
+}
+
+#[test]
+fn builds_the_placeholder_in_batches() {
+    let mut app = app_with_policy(MissingPrototypePolicy::Placeholder(Id::from_name("Bar")));
+    let entities = app
+        .world_mut()
+        .spawn_prototype_batch(Id::<Item>::from_name("Nope"), 2);
+
+    for entity in entities {
+        assert_eq!(app.world().get::<Val>(entity), Some(&Val(2)));
+    }
+}
+
+#[test]
+fn leaves_the_entity_empty_if_the_placeholder_is_missing() {
+    let mut app = app_with_policy(MissingPrototypePolicy::Placeholder(Id::from_name("Gone")));
+    let entity = app.world_mut().spawn_prototype_named::<Item>("Nope").id();
+
+    assert!(app.world().get::<Val>(entity).is_none());
+}
+
+#[test]
+fn finds_prototypes_that_exist_regardless_of_the_policy() {
+    let mut app = app_with_policy(MissingPrototypePolicy::Panic);
+    let entity = app.world_mut().spawn_prototype_named::<Item>("Bar").id();
+
+    assert_eq!(app.world().get::<Val>(entity), Some(&Val(2)));
+}
@@ -0,0 +1,186 @@
+//! Tests for applying prototypes to existing entities, and replacing them
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+struct FlyManifest {
+    name: String,
+}
+
+impl Manifest for FlyManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    type Output = Fly;
+
+    fn reify(&self) -> Fly {
+        Fly {
+            name: self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct Fly {
+    name: String,
+}
+
+#[derive(Component)]
+struct Wings;
+
+impl Prototype for Fly {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn build(&self, target: &mut EntityWorldMut) {
+        target.insert((Wings, Transform::from_scale(Vec3::splat(0.5))));
+    }
+
+    fn content_hash(&self) -> u64 {
+        hash_of(self)
+    }
+}
+
+fn loaded_app() -> App {
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    app.add_prototype::<FlyManifest, Fly>("fly/a.ron");
+    run_until_loaded::<Item>(&mut app, 2);
+    run_until_loaded::<Fly>(&mut app, 1);
+    app
+}
+
+fn component_id<C: Component>(app: &App) -> bevy::ecs::component::ComponentId {
+    app.world().component_id::<C>().unwrap()
+}
+
+#[test]
+fn apply_tracks_overwritten_components_as_changed() {
+    let mut app = loaded_app();
+    let entity = app.world_mut().spawn(Transform::default()).id();
+    app.world_mut()
+        .entity_mut(entity)
+        .apply_prototype_named::<Fly>("Butterfly");
+
+    let tracking = app.world().get::<PrototypeComponents>(entity).unwrap();
+    assert_eq!(
+        tracking.added().collect::<Vec<_>>(),
+        [component_id::<Wings>(&app)]
+    );
+    assert_eq!(
+        tracking.changed().collect::<Vec<_>>(),
+        [component_id::<Transform>(&app)]
+    );
+    assert_eq!(
+        app.world().get::<FromPrototype<Fly>>(entity).unwrap().0,
+        Id::from_name("Butterfly")
+    );
+}
+
+#[test]
+fn apply_with_commands_keeps_existing_components_on_replace() {
+    let mut app = loaded_app();
+    let entity = app.world_mut().spawn(Transform::default()).id();
+    let fly = app
+        .world()
+        .resource::<PrototypeLibrary<Fly>>()
+        .get_by_name("Butterfly")
+        .unwrap();
+    app.world_mut()
+        .commands()
+        .entity(entity)
+        .apply_prototype(fly)
+        .replace_prototype(Id::<Item>::from_name("Bar"));
+    app.world_mut().flush();
+
+    assert!(app.world().get::<Transform>(entity).is_some());
+    assert!(app.world().get::<Wings>(entity).is_none());
+    assert_eq!(app.world().get::<Val>(entity), Some(&Val(2)));
+}
+
+#[test]
+fn replace_swaps_prototypes_of_the_same_type() {
+    let mut app = loaded_app();
+    let entity = app
+        .world_mut()
+        .spawn_prototype_named::<Item>("Foo")
+        .insert(Transform::default())
+        .id();
+
+    app.world_mut()
+        .entity_mut(entity)
+        .replace_prototype(Id::<Item>::from_name("Bar"));
+
+    assert_eq!(app.world().get::<Val>(entity), Some(&Val(2)));
+    assert!(app.world().get::<Transform>(entity).is_some());
+    assert_eq!(
+        app.world().get::<FromPrototype<Item>>(entity).unwrap().0,
+        Id::from_name("Bar")
+    );
+    let replaced: Vec<_> = app
+        .world_mut()
+        .resource_mut::<Events<PrototypeReplaced<Item>>>()
+        .drain()
+        .collect();
+    assert_eq!(replaced.len(), 1);
+    assert_eq!(replaced[0].entity, entity);
+    assert_eq!(replaced[0].previous_name.as_deref(), Some("Foo"));
+}
+
+#[test]
+fn replace_removes_only_components_the_prototype_added() {
+    let mut app = loaded_app();
+    let entity = app
+        .world_mut()
+        .spawn((Transform::from_xyz(1.0, 2.0, 3.0), Name::new("Bug")))
+        .id();
+    app.world_mut()
+        .entity_mut(entity)
+        .apply_prototype_named::<Fly>("Butterfly");
+
+    app.world_mut()
+        .entity_mut(entity)
+        .replace_prototype(Id::<Item>::from_name("Foo"));
+
+    let world = app.world();
+    assert!(world.get::<Wings>(entity).is_none());
+    assert!(world.get::<FromPrototype<Fly>>(entity).is_none());
+    assert_eq!(world.get::<Val>(entity), Some(&Val(1)));
+    assert_eq!(world.get::<Name>(entity).unwrap().as_str(), "Bug");
+    // The butterfly overwrote the transform, which is kept as it was
+    let transform = world.get::<Transform>(entity).unwrap();
+    assert_eq!(*transform, Transform::from_scale(Vec3::splat(0.5)));
+
+    let replaced: Vec<_> = app
+        .world_mut()
+        .resource_mut::<Events<PrototypeReplaced<Item>>>()
+        .drain()
+        .collect();
+    assert_eq!(replaced[0].previous_name.as_deref(), Some("Butterfly"));
+    assert_eq!(
+        replaced[0].previous_type,
+        Some(std::any::type_name::<Fly>())
+    );
+}
+
+#[test]
+fn replace_removes_components_the_prototype_added_itself() {
+    let mut app = loaded_app();
+    let entity = app
+        .world_mut()
+        .spawn_prototype_named::<Fly>("Butterfly")
+        .id();
+
+    app.world_mut()
+        .entity_mut(entity)
+        .replace_prototype(Id::<Item>::from_name("Foo"));
+
+    // The butterfly added its transform, so it goes with it
+    assert!(app.world().get::<Transform>(entity).is_none());
+    assert!(app.world().get::<Wings>(entity).is_none());
+    assert_eq!(app.world().get::<Val>(entity), Some(&Val(1)));
+}
@@ -0,0 +1,133 @@
+//! Tests for counting the progress of loading manifests
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+
+fn progress_for<M: Manifest>(app: &App) -> ProtoProgress {
+    app.world().resource::<ManifestLoader>().progress_for::<M>()
+}
+
+fn all_ready(app: &mut App) -> bool {
+    app.world()
+        .resource::<ManifestLoader>()
+        .all_prototypes_ready()
+}
+
+#[test]
+fn counts_files_per_manifest_as_they_load() {
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    app.add_prototype::<ItemManifest, Item>("items/b.ron");
+    app.add_prototype::<MonsterManifest, Monster>("refs/e.ron");
+    assert_eq!(
+        progress_for::<ItemManifest>(&app),
+        ProtoProgress { done: 0, total: 2 }
+    );
+    assert_eq!(
+        progress_for::<MonsterManifest>(&app),
+        ProtoProgress { done: 0, total: 1 }
+    );
+
+    let mut seen = Vec::new();
+    assert!(run_until(&mut app, |app| {
+        seen.push(progress_for::<ItemManifest>(app));
+        all_ready(app)
+    }));
+    assert!(seen.windows(2).all(|w| w[0].done <= w[1].done));
+    assert!(seen.iter().all(|progress| progress.total == 2));
+
+    assert_eq!(
+        progress_for::<ItemManifest>(&app),
+        ProtoProgress { done: 2, total: 2 }
+    );
+    assert_eq!(
+        progress_for::<MonsterManifest>(&app),
+        ProtoProgress { done: 1, total: 1 }
+    );
+    app.update();
+    assert_eq!(
+        *app.world().resource::<ProtoProgress>(),
+        ProtoProgress { done: 3, total: 3 }
+    );
+}
+
+#[test]
+fn counts_a_folder_as_one_file_until_it_is_listed() {
+    let mut app = app();
+    app.add_prototype_folder::<ItemManifest, Item>("items");
+    assert_eq!(
+        progress_for::<ItemManifest>(&app),
+        ProtoProgress { done: 0, total: 1 }
+    );
+
+    assert!(run_until(&mut app, all_ready));
+    assert_eq!(
+        progress_for::<ItemManifest>(&app),
+        ProtoProgress { done: 3, total: 3 }
+    );
+}
+
+#[test]
+fn counts_failed_files_as_done() {
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    app.add_prototype::<ItemManifest, Item>("bad/x.ron");
+
+    assert!(run_until(&mut app, |app| app
+        .world()
+        .resource::<ManifestLoader>()
+        .is_all_processed()));
+    assert_eq!(
+        progress_for::<ItemManifest>(&app),
+        ProtoProgress { done: 2, total: 2 }
+    );
+}
+
+#[cfg(feature = "progress_tracking")]
+mod progress_tracking {
+    use super::*;
+    use iyes_progress::{Progress, ProgressCounter, ProgressPlugin, TrackedProgressSet};
+
+    #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+    enum GameState {
+        #[default]
+        Loading,
+        Menu,
+    }
+
+    /// The progress counted by iyes_progress each frame while loading
+    #[derive(Resource, Default)]
+    struct Counted(Vec<Progress>);
+
+    #[test]
+    fn reports_progress_to_iyes_progress() {
+        let mut app = app();
+        app.init_state::<GameState>();
+        app.add_plugins(ProgressPlugin::new(GameState::Loading).continue_to(GameState::Menu));
+        app.init_resource::<Counted>();
+        app.add_systems(
+            Last,
+            (|counter: Res<ProgressCounter>, mut counted: ResMut<Counted>| {
+                counted.0.push(counter.progress());
+            })
+            .after(TrackedProgressSet)
+            .run_if(resource_exists::<ProgressCounter>),
+        );
+        app.add_prototype::<ItemManifest, Item>("items/a.ron");
+        app.add_prototype::<ItemManifest, Item>("items/b.ron");
+
+        assert!(run_until(&mut app, |app| *app
+            .world()
+            .resource::<State<GameState>>()
+            .get()
+            == GameState::Menu));
+        assert_eq!(app.world().resource::<PrototypeLibrary<Item>>().len(), 3);
+        let counted = &app.world().resource::<Counted>().0;
+        assert!(counted.iter().all(|progress| progress.total == 2));
+        assert_eq!(counted.first().map(|p| p.done), Some(0));
+        assert_eq!(counted.last().map(|p| p.done), Some(2));
+    }
+}
@@ -0,0 +1,70 @@
+//! Tests for validating references between prototypes
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use rantz_proto::prelude::*;
+
+/// The dangling references sent so far
+#[derive(Resource, Default)]
+struct Dangling(Vec<DanglingReference>);
+
+fn collect_dangling(mut events: EventReader<DanglingReference>, mut dangling: ResMut<Dangling>) {
+    dangling.0.extend(events.read().cloned());
+}
+
+fn app_with_monsters(dir: impl Into<String>, watch: bool) -> App {
+    let mut app = app_in(dir, watch);
+    app.init_resource::<Dangling>();
+    app.add_systems(PostUpdate, collect_dangling);
+    app
+}
+
+fn dangling(app: &App) -> &[DanglingReference] {
+    &app.world().resource::<Dangling>().0
+}
+
+#[test]
+fn reports_references_to_missing_prototypes() {
+    let mut app = app_with_monsters(ASSETS, false);
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    app.add_prototype::<MonsterManifest, Monster>("refs/e.ron");
+    assert!(run_until(&mut app, |app| !dangling(app).is_empty()));
+
+    let reference = &dangling(&app)[0];
+    assert_eq!(dangling(&app).len(), 1);
+    assert_eq!(reference.source, "Goblin");
+    assert_eq!(reference.field, "loot");
+    assert_eq!(reference.target, "Nope");
+    assert!(reference.target_type.ends_with("Item"));
+}
+
+#[cfg(feature = "hot_reload")]
+#[test]
+fn validates_references_again_after_a_reload() {
+    let dir = temp_dir("references_reload");
+    write(
+        dir.join("items.ron"),
+        r#"(prototypes: [(name: "Foo", i: 1)])"#,
+    );
+    write(
+        dir.join("monsters.ron"),
+        r#"(prototypes: [(name: "Goblin", loot: ["Foo"])])"#,
+    );
+    let mut app = app_with_monsters(dir.to_string_lossy(), true);
+    app.add_prototype::<ItemManifest, Item>("items.ron");
+    app.add_prototype::<MonsterManifest, Monster>("monsters.ron");
+    run_until_loaded::<Monster>(&mut app, 1);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(dangling(&app).is_empty());
+
+    write(
+        dir.join("monsters.ron"),
+        r#"(prototypes: [(name: "Goblin", loot: ["Foo", "Bar"])])"#,
+    );
+    assert!(run_until(&mut app, |app| !dangling(app).is_empty()));
+    assert_eq!(dangling(&app)[0].target, "Bar");
+}
@@ -0,0 +1,181 @@
+//! Tests for spawning prototypes with overrides, and asynchronously
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::{
+    ecs::{system::RunSystemOnce, world::CommandQueue},
+    prelude::*,
+    tasks::futures_lite::future,
+};
+use common::*;
+use rantz_proto::prelude::*;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    task::{Poll, Waker},
+};
+
+fn foo() -> Item {
+    Item {
+        name: "Foo".to_string(),
+        i: 1,
+    }
+}
+
+/// Asserts the entity was built from Foo, with its value overridden and a name added
+fn assert_overridden(app: &App, entity: Entity) {
+    let entity = app.world().entity(entity);
+    assert_eq!(entity.get::<Val>(), Some(&Val(7)));
+    assert_eq!(entity.get::<Name>().unwrap().as_str(), "Chest");
+    assert_eq!(
+        entity.get::<FromPrototype<Item>>().unwrap().0,
+        Id::from_name("Foo")
+    );
+}
+
+#[test]
+fn world_inserts_the_bundle_after_building() {
+    let mut app = app();
+    let entity = app
+        .world_mut()
+        .spawn_prototype_with(foo(), (Val(7), Name::new("Chest")))
+        .id();
+
+    assert_overridden(&app, entity);
+}
+
+#[test]
+fn commands_insert_the_bundle_after_building() {
+    let mut app = app();
+    let entity = app.world_mut().run_system_once(|mut commands: Commands| {
+        commands
+            .spawn_prototype_with(foo(), (Val(7), Name::new("Chest")))
+            .id()
+    });
+
+    assert_overridden(&app, entity);
+}
+
+#[test]
+fn command_queues_insert_the_bundle_after_building() {
+    let mut app = app();
+    let mut queue = CommandQueue::default();
+    queue.spawn_prototype_with(foo(), (Val(7), Name::new("Chest")));
+    queue.apply(app.world_mut());
+
+    let entity = app
+        .world_mut()
+        .query_filtered::<Entity, With<Val>>()
+        .single(app.world());
+    assert_overridden(&app, entity);
+}
+
+#[test]
+fn spawned_entities_can_be_chained() {
+    let mut app = app();
+    let entity = app
+        .world_mut()
+        .spawn_prototype(foo())
+        .insert(Name::new("Chest"))
+        .id();
+
+    let entity = app.world().entity(entity);
+    assert_eq!(entity.get::<Val>(), Some(&Val(1)));
+    assert!(entity.contains::<Name>());
+}
+
+#[test]
+fn builds_async_spawns_from_the_library_on_their_placeholder() {
+    let mut app = app();
+    app.add_prototype::<ItemManifest, Item>("items/a.ron");
+    run_until_loaded::<Item>(&mut app, 2);
+    let bar = app
+        .world()
+        .resource::<PrototypeLibrary<Item>>()
+        .get_by_name("Bar")
+        .unwrap();
+
+    let entity = app.world_mut().spawn_prototype_async(bar).id();
+    assert!(!app.world().entity(entity).contains::<Val>());
+    app.world_mut()
+        .entity_mut(entity)
+        .insert(Name::new("Waiting"));
+
+    assert!(run_until(&mut app, |app| app
+        .world()
+        .entity(entity)
+        .contains::<Val>()));
+    let entity = app.world().entity(entity);
+    assert_eq!(entity.get::<Val>(), Some(&Val(2)));
+    assert_eq!(
+        entity.get::<FromPrototype<Item>>().unwrap().0,
+        Id::from_name("Bar")
+    );
+    assert_eq!(entity.get::<Name>().unwrap().as_str(), "Waiting");
+    assert_eq!(
+        app.world_mut()
+            .query_filtered::<Entity, With<Val>>()
+            .iter(app.world())
+            .count(),
+        1
+    );
+}
+
+/// Opened to let [`Gated`] finish preparing
+static OPEN: AtomicBool = AtomicBool::new(false);
+static WAKER: Mutex<Option<Waker>> = Mutex::new(None);
+/// Set once [`Gated`] has finished preparing
+static PREPARED: AtomicBool = AtomicBool::new(false);
+
+/// A prototype that takes until [`OPEN`] is set to prepare
+#[derive(Debug, Clone, Hash)]
+struct Gated;
+
+impl Prototype for Gated {
+    fn name(&self) -> String {
+        "Gated".to_string()
+    }
+
+    fn build(&self, target: &mut EntityWorldMut) {
+        target.insert(Val(0));
+    }
+
+    async fn prepare(self) -> Self {
+        future::poll_fn(|cx| {
+            if OPEN.load(Ordering::Acquire) {
+                return Poll::Ready(());
+            }
+            *WAKER.lock().unwrap() = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await;
+        PREPARED.store(true, Ordering::Release);
+        self
+    }
+
+    fn content_hash(&self) -> u64 {
+        hash_of(self)
+    }
+}
+
+#[test]
+fn despawning_the_placeholder_cancels_the_async_spawn() {
+    let mut app = app();
+    let entity = app.world_mut().spawn_prototype_async(Gated).id();
+    app.update();
+    app.world_mut().despawn(entity);
+
+    OPEN.store(true, Ordering::Release);
+    if let Some(waker) = WAKER.lock().unwrap().take() {
+        waker.wake();
+    }
+    for _ in 0..20 {
+        app.update();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    assert!(!PREPARED.load(Ordering::Acquire));
+    assert_eq!(app.world_mut().query::<&Val>().iter(app.world()).count(), 0);
+}
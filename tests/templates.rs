@@ -0,0 +1,265 @@
+//! Tests for spawning prototypes from templates
+#![cfg(feature = "ron")]
+mod common;
+
+use bevy::{prelude::*, utils::HashMap};
+use common::*;
+use rantz_proto::prelude::*;
+use serde::Deserialize;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+struct WidgetManifest {
+    name: String,
+    val: i32,
+    child: Option<i32>,
+    opaque: bool,
+    #[serde(default)]
+    themed: bool,
+}
+
+impl Manifest for WidgetManifest {
+    const FORMAT: ManifestFormat = ManifestFormat::Ron;
+    type Output = Widget;
+
+    fn reify(&self) -> Widget {
+        Widget {
+            name: self.name.clone(),
+            val: self.val,
+            child: self.child,
+            opaque: self.opaque,
+            themed: self.themed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct Widget {
+    name: String,
+    val: i32,
+    child: Option<i32>,
+    opaque: bool,
+    /// Whether the widget's value is added to the [`Theme`]'s
+    themed: bool,
+}
+
+/// How many times each prototype was built. Templates are built outside of the app's world,
+/// so this can't be a resource. Each test uses its own prototype names
+static BUILDS: Mutex<Option<HashMap<String, usize>>> = Mutex::new(None);
+
+fn builds(name: &str) -> usize {
+    BUILDS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|builds| builds.get(name).copied())
+        .unwrap_or(0)
+}
+
+/// A component that isn't registered for reflection
+#[derive(Component)]
+struct Opaque;
+
+/// A resource themed widgets read when they're built
+#[derive(Resource)]
+struct Theme(i32);
+
+impl Prototype for Widget {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn build(&self, target: &mut EntityWorldMut) {
+        *BUILDS
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .entry(self.name.clone())
+            .or_default() += 1;
+        if self.themed {
+            let theme = target.world().resource::<Theme>().0;
+            target.insert(Val(self.val + theme));
+        } else {
+            target.insert(Val(self.val));
+        }
+        if self.opaque {
+            target.insert(Opaque);
+        }
+        if let Some(child) = self.child {
+            target.with_children(|parent| {
+                parent.spawn(Val(child));
+            });
+        }
+    }
+
+    fn content_hash(&self) -> u64 {
+        hash_of(self)
+    }
+}
+
+fn widgets(name: &str) -> String {
+    format!(
+        r#"(prototypes: [
+            (name: "{name}", val: 1, child: Some(2), opaque: false),
+            (name: "{name}_opaque", val: 3, child: None, opaque: true),
+        ])"#
+    )
+}
+
+/// An app with templates for widgets, which has loaded the widgets named after `name`
+fn loaded_app(name: &str, watch: bool) -> (App, std::path::PathBuf) {
+    let (mut app, dir) = app_with_files(name, &[("widgets.ron", &widgets(name))], watch);
+    app.register_type::<Val>();
+    app.add_prototype::<WidgetManifest, Widget>("widgets.ron");
+    app.use_prototype_templates::<Widget>();
+    run_until_loaded::<Widget>(&mut app, 2);
+    app.update();
+    (app, dir)
+}
+
+fn library(app: &App) -> &PrototypeLibrary<Widget> {
+    app.world().resource::<PrototypeLibrary<Widget>>()
+}
+
+/// The value of the entity's [`Val`], and of its children's
+fn vals(app: &App, entity: Entity) -> (i32, Vec<i32>) {
+    let children = app
+        .world()
+        .get::<Children>(entity)
+        .map(|children| {
+            children
+                .iter()
+                .map(|&child| app.world().get::<Val>(child).unwrap().0)
+                .collect()
+        })
+        .unwrap_or_default();
+    (app.world().get::<Val>(entity).unwrap().0, children)
+}
+
+#[test]
+fn clones_the_template_and_its_children() {
+    let (mut app, _) = loaded_app("template_clone", false);
+    let id = Id::<Widget>::from_name("template_clone");
+    assert!(library(&app).has_template(&id));
+    assert_eq!(builds("template_clone"), 1);
+
+    let first = app.world_mut().spawn_prototype_id(id).id();
+    let second = app.world_mut().spawn_prototype_id(id).id();
+
+    assert_eq!(builds("template_clone"), 1);
+    assert_eq!(vals(&app, first), (1, vec![2]));
+    assert_eq!(vals(&app, second), (1, vec![2]));
+    let children = |entity| app.world().get::<Children>(entity).unwrap()[0];
+    assert_ne!(children(first), children(second));
+    assert_eq!(
+        app.world().get::<FromPrototype<Widget>>(first).unwrap().0,
+        id
+    );
+}
+
+#[test]
+fn builds_templates_outside_of_the_app_world() {
+    #[derive(Resource, Default)]
+    struct Added(usize);
+
+    let (mut app, _) = app_with_files(
+        "template_scratch",
+        &[("widgets.ron", &widgets("template_scratch"))],
+        false,
+    );
+    app.register_type::<Val>();
+    app.init_resource::<Added>();
+    app.observe(|_: Trigger<OnAdd, Val>, mut added: ResMut<Added>| added.0 += 1);
+    app.add_prototype::<WidgetManifest, Widget>("widgets.ron");
+    app.use_prototype_templates::<Widget>();
+    run_until_loaded::<Widget>(&mut app, 2);
+    app.update();
+
+    assert_eq!(builds("template_scratch"), 1);
+    assert_eq!(app.world().resource::<Added>().0, 0);
+}
+
+#[test]
+fn uses_the_template_for_equal_prototypes_spawned_by_value() {
+    let (mut app, _) = loaded_app("template_value", false);
+    let mut widget = library(&app).get_by_name("template_value").unwrap();
+
+    let same = app.world_mut().spawn_prototype(widget.clone()).id();
+    assert_eq!(builds("template_value"), 1);
+    assert_eq!(vals(&app, same), (1, vec![2]));
+
+    widget.val = 5;
+    let changed = app.world_mut().spawn_prototype(widget).id();
+    assert_eq!(builds("template_value"), 2);
+    assert_eq!(vals(&app, changed), (5, vec![2]));
+}
+
+#[test]
+fn builds_every_spawn_if_a_component_cant_be_cloned() {
+    let (mut app, _) = loaded_app("template_opaque", false);
+    let id = Id::<Widget>::from_name("template_opaque_opaque");
+    assert!(!library(&app).has_template(&id));
+
+    let entity = app.world_mut().spawn_prototype_id(id).id();
+    assert!(app.world().get::<Opaque>(entity).is_some());
+    assert_eq!(builds("template_opaque_opaque"), 2);
+}
+
+#[test]
+fn builds_every_spawn_if_building_the_template_panics() {
+    let (mut app, _) = app_with_files(
+        "template_panic",
+        &[(
+            "widgets.ron",
+            r#"(prototypes: [(name: "template_panic", val: 1, child: None, opaque: false, themed: true)])"#,
+        )],
+        false,
+    );
+    app.register_type::<Val>();
+    app.insert_resource(Theme(10));
+    app.add_prototype::<WidgetManifest, Widget>("widgets.ron");
+    app.use_prototype_templates::<Widget>();
+    run_until_loaded::<Widget>(&mut app, 1);
+    app.update();
+
+    let id = Id::<Widget>::from_name("template_panic");
+    assert!(!library(&app).has_template(&id));
+    let entity = app.world_mut().spawn_prototype_id(id).id();
+    assert_eq!(builds("template_panic"), 2);
+    assert_eq!(vals(&app, entity), (11, vec![]));
+}
+
+#[test]
+fn rebuilds_templates_when_a_prototype_changes() {
+    let (mut app, _) = loaded_app("template_changed", false);
+    let id = Id::<Widget>::from_name("template_changed");
+    app.world_mut()
+        .resource_mut::<PrototypeLibrary<Widget>>()
+        .get_mut(&id)
+        .unwrap()
+        .val = 4;
+    app.update();
+
+    assert_eq!(builds("template_changed"), 2);
+    let entity = app.world_mut().spawn_prototype_id(id).id();
+    assert_eq!(builds("template_changed"), 2);
+    assert_eq!(vals(&app, entity), (4, vec![2]));
+}
+
+#[cfg(feature = "hot_reload")]
+#[test]
+fn rebuilds_templates_when_a_prototype_is_reloaded() {
+    let (mut app, dir) = loaded_app("template_reload", true);
+    write(
+        dir.join("widgets.ron"),
+        r#"(prototypes: [(name: "template_reload", val: 6, child: None, opaque: false)])"#,
+    );
+    run_until_loaded::<Widget>(&mut app, 1);
+    app.update();
+
+    let builds_after_reload = builds("template_reload");
+    let id = Id::<Widget>::from_name("template_reload");
+    let entity = app.world_mut().spawn_prototype_id(id).id();
+    assert_eq!(builds("template_reload"), builds_after_reload);
+    assert_eq!(vals(&app, entity), (6, vec![]));
+}